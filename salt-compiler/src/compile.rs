@@ -1,5 +1,8 @@
 use std::fs::remove_file;
-use std::process::Command;
+use std::io::{BufRead, BufReader};
+use std::process::{Command, Stdio};
+
+use crate::mangle::demangle_text;
 
 pub fn compile() {
     compile_llvm_ir();
@@ -29,18 +32,13 @@ fn assemble_nasm() {
 }
 
 fn link_elf() {
-    Command::new("ld")
-        .args([
-            "-T",
-            "linker.ld",
-            "../salt-stdlib/target/release",
-            "-o",
-            "iso/boot/kernel.elf",
-        ])
-        .spawn()
-        .unwrap()
-        .wait()
-        .unwrap();
+    run_demangled(Command::new("ld").args([
+        "-T",
+        "linker.ld",
+        "../salt-stdlib/target/release",
+        "-o",
+        "iso/boot/kernel.elf",
+    ]));
 }
 fn make_iso() {
     Command::new("grub-mkrescue")
@@ -58,10 +56,32 @@ fn clean_up() {
 }
 
 fn run_qemu() {
-    Command::new("qemu-system-x86_64")
-        .args(["-cdrom", "saltos.iso"])
+    run_demangled(Command::new("qemu-system-x86_64").args([
+        "-cdrom",
+        "saltos.iso",
+        "-serial",
+        "stdio",
+    ]));
+}
+
+// Runs the command with any Salt symbols in its output demangled
+fn run_demangled(command: &mut Command) {
+    let mut child = command
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
         .spawn()
-        .unwrap()
-        .wait()
         .unwrap();
+
+    let stderr = BufReader::new(child.stderr.take().unwrap());
+    let stderr = std::thread::spawn(move || {
+        for line in stderr.lines() {
+            eprintln!("{}", demangle_text(&line.unwrap()));
+        }
+    });
+    for line in BufReader::new(child.stdout.take().unwrap()).lines() {
+        println!("{}", demangle_text(&line.unwrap()));
+    }
+
+    stderr.join().unwrap();
+    child.wait().unwrap();
 }
@@ -1,12 +1,52 @@
+use std::ops::Range;
+
 use thiserror::Error;
 
-use crate::lexer::{Location, Token, TokenKind, Type};
+use crate::{
+    instruction::attribute::Target,
+    lexer::{Location, Token, TokenKind, Type},
+    macros::DEPTH,
+    type_checker::Access,
+};
 
 #[derive(Default, Debug, Clone, PartialEq, Error)]
 pub enum LexingError {
     #[error("error: invalid character `{0}`")]
     UnexpectedCharacter(char),
 
+    #[error("error: unterminated block comment")]
+    UnterminatedBlockComment,
+
+    #[error("error: unterminated double quote string")]
+    UnterminatedString,
+    #[error("error: unterminated character literal")]
+    UnterminatedCharacterLiteral,
+    #[error("error: empty character literal")]
+    EmptyCharacterLiteral,
+    #[error("error: character literal may only contain one codepoint")]
+    MultipleCharacterLiteral,
+
+    #[error("error: no valid digits found for number")]
+    MissingDigits,
+    #[error("error: integer literal is too large")]
+    IntegerTooLarge,
+
+    // The spans of escape errors are relative to the start of the literal
+    #[error("error: unknown character escape `{escape}`")]
+    UnknownEscape { escape: String, span: Range<usize> },
+    #[error("error: out of range hex escape `{escape}`, must be at most `\\x7F`")]
+    OutOfRangeHexEscape { escape: String, span: Range<usize> },
+    #[error("error: invalid hex escape `{escape}`")]
+    InvalidHexEscape { escape: String, span: Range<usize> },
+    #[error("error: invalid unicode escape `{escape}`")]
+    InvalidUnicodeEscape { escape: String, span: Range<usize> },
+    #[error("error: unicode escape `{escape}` in byte literal")]
+    UnicodeEscapeInByteLiteral { escape: String, span: Range<usize> },
+    #[error("error: non-ASCII character `{character}` in byte literal")]
+    NonAsciiByte { character: char, span: Range<usize> },
+    #[error("error: null characters in C string literals are not supported")]
+    NulInCString,
+
     #[default]
     #[error("")]
     Other,
@@ -16,12 +56,46 @@ impl LexingError {
     pub fn from_lexer(lex: &mut logos::Lexer<'_, TokenKind>) -> Self {
         LexingError::UnexpectedCharacter(lex.slice().chars().next().unwrap())
     }
+
+    pub fn span(&self) -> Option<Range<usize>> {
+        match self {
+            LexingError::UnknownEscape { span, .. }
+            | LexingError::OutOfRangeHexEscape { span, .. }
+            | LexingError::InvalidHexEscape { span, .. }
+            | LexingError::InvalidUnicodeEscape { span, .. }
+            | LexingError::UnicodeEscapeInByteLiteral { span, .. }
+            | LexingError::NonAsciiByte { span, .. } => Some(span.clone()),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+pub struct LexError {
+    pub error: LexingError,
+    pub location: Location,
+}
+
+impl std::fmt::Display for LexError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}", self.error)?;
+        writeln!(f)?;
+        writeln!(f, "{}", self.location)?;
+        Ok(())
+    }
 }
 
 #[derive(Debug)]
 pub enum ExpectedToken {
     Statement,
     ImportSymbol,
+    Abi,
+    PointerMutability,
+    Field,
+    AttributeArgument,
+    Fragment,
+    MacroDelimiter,
+    FormatString,
     Specific { kind: TokenKind },
 }
 
@@ -30,6 +104,16 @@ impl std::fmt::Display for ExpectedToken {
         match self {
             ExpectedToken::Statement => write!(f, "statement"),
             ExpectedToken::ImportSymbol => write!(f, "identifier or `::`"),
+            ExpectedToken::Abi => write!(f, "ABI `\"C\"`"),
+            ExpectedToken::PointerMutability => write!(f, "`mut` or `const`"),
+            ExpectedToken::Field => write!(f, "tuple index"),
+            ExpectedToken::AttributeArgument => write!(f, "identifier or string literal"),
+            ExpectedToken::Fragment => write!(
+                f,
+                "fragment specifier `expr`, `ident`, `ty`, `literal`, `block` or `tt`"
+            ),
+            ExpectedToken::MacroDelimiter => write!(f, "`(`, `[` or `{{`"),
+            ExpectedToken::FormatString => write!(f, "format string literal"),
             ExpectedToken::Specific { kind } => write!(f, "{kind}"),
         }
     }
@@ -43,6 +127,29 @@ pub enum ParseError {
         actual: Box<Token>,
         expected: ExpectedToken,
     },
+
+    UndefinedMacro(Location),
+    NoMatchingRule {
+        location: Location,
+        // The name of the macro in its definition
+        definition: Box<Location>,
+    },
+    UnboundMacroVariable(Location),
+    RepeatingMacroVariable(Location),
+    MacroRepetition(Location),
+    MacroRecursionLimit(Location),
+
+    InvalidPlaceholder {
+        location: Location,
+        placeholder: Box<str>,
+    },
+    FormatArguments {
+        location: Location,
+        placeholders: usize,
+        arguments: usize,
+    },
+
+    UnknownIntrinsic(Location),
 }
 
 impl std::fmt::Display for ParseError {
@@ -55,6 +162,122 @@ impl std::fmt::Display for ParseError {
                 writeln!(f, "{}", actual.location)?;
                 Ok(())
             }
+            ParseError::UndefinedMacro(location) => {
+                writeln!(f, "error: cannot find macro `{}!`", location.value())?;
+                writeln!(f)?;
+                writeln!(f, "{}", location)?;
+                writeln!(f, "note: a macro has to be defined before it is used")?;
+                Ok(())
+            }
+            ParseError::NoMatchingRule {
+                location,
+                definition,
+            } => {
+                writeln!(
+                    f,
+                    "error: no rule of macro `{}!` matches these arguments",
+                    definition.value()
+                )?;
+                writeln!(f)?;
+                writeln!(f, "{}", location)?;
+                writeln!(f, "note: the macro is defined here")?;
+                writeln!(f)?;
+                writeln!(f, "{}", definition)?;
+                Ok(())
+            }
+            ParseError::UnboundMacroVariable(location) => {
+                writeln!(
+                    f,
+                    "error: `{}` is not captured by the pattern of this rule",
+                    location.value()
+                )?;
+                writeln!(f)?;
+                writeln!(f, "{}", location)?;
+                Ok(())
+            }
+            ParseError::RepeatingMacroVariable(location) => {
+                writeln!(f, "error: `{}` is still repeating here", location.value())?;
+                writeln!(f)?;
+                writeln!(f, "{}", location)?;
+                writeln!(
+                    f,
+                    "note: it is captured inside `$(...)*` and has to be used inside one"
+                )?;
+                Ok(())
+            }
+            ParseError::MacroRepetition(location) => {
+                writeln!(f, "error: can not tell how many times to repeat this")?;
+                writeln!(f)?;
+                writeln!(f, "{}", location)?;
+                writeln!(
+                    f,
+                    "note: it has to use variables captured inside `$(...)*` the same number of times"
+                )?;
+                Ok(())
+            }
+            ParseError::MacroRecursionLimit(location) => {
+                writeln!(
+                    f,
+                    "error: recursion limit reached while expanding `{}!`",
+                    location.value()
+                )?;
+                writeln!(f)?;
+                writeln!(f, "{}", location)?;
+                writeln!(
+                    f,
+                    "note: macros can expand to invocations of macros {} deep",
+                    DEPTH
+                )?;
+                Ok(())
+            }
+            ParseError::InvalidPlaceholder {
+                location,
+                placeholder,
+            } => {
+                writeln!(
+                    f,
+                    "error: invalid placeholder `{}` in format string",
+                    placeholder
+                )?;
+                writeln!(f)?;
+                writeln!(f, "{}", location)?;
+                writeln!(
+                    f,
+                    "note: the placeholders are `{{}}` and `{{:x}}`, `{{{{` and `}}}}` are braces"
+                )?;
+                Ok(())
+            }
+            ParseError::FormatArguments {
+                location,
+                placeholders,
+                arguments,
+            } => {
+                writeln!(
+                    f,
+                    "error: {} {} in format string, but {} {} given",
+                    placeholders,
+                    if *placeholders == 1 {
+                        "placeholder"
+                    } else {
+                        "placeholders"
+                    },
+                    arguments,
+                    if *arguments == 1 {
+                        "argument is"
+                    } else {
+                        "arguments are"
+                    }
+                )?;
+                writeln!(f)?;
+                writeln!(f, "{}", location)?;
+                Ok(())
+            }
+            ParseError::UnknownIntrinsic(location) => {
+                writeln!(f, "error: unknown intrinsic `{}`", location.value())?;
+                writeln!(f)?;
+                writeln!(f, "{}", location)?;
+                Ok(())
+            }
         }
     }
 }
@@ -63,15 +286,185 @@ impl std::fmt::Display for ParseError {
 pub enum TypeCheckError {
     UnsafeUse(Location),
     UnsafeUseNoToken,
+    UnsafeCall {
+        location: Location,
+        // The name of the `raw fn`
+        declaration: Box<Location>,
+    },
+    UnsafeCallNoToken {
+        declaration: Box<Location>,
+    },
 
     UndeclaredFunction(Location),
     UndeclaredFunctionNoToken,
+    NotConstCall(Location),
+    NotConstCallNoToken,
+
+    UndeclaredVariable(Location),
+    UndeclaredVariableNoToken,
+    UndeclaredType {
+        name: Box<str>,
+        location: Location,
+    },
+
+    UnknownTrait(Location),
+    InvalidDrop(Location),
+
+    UseOfMovedValue {
+        location: Location,
+        // Where the value was moved, `None` when it was moved by the loop around it
+        moved: Option<Box<Location>>,
+    },
+    MoveOutOfDrop(Location),
+    MoveOutOfReference(Location),
+    MoveOutOfReferenceNoToken,
+    MoveOutOfStatic(Location),
+
+    NotAPlace(Location),
+    ImmutablePlace(Location),
+    BorrowConflict {
+        location: Location,
+        // Where the borrow that is in the way was taken
+        loan: Box<Location>,
+        access: Access,
+    },
+    DoesNotLiveLongEnough {
+        location: Location,
+        // Where the borrowed binding went out of scope and the use of the reference after it,
+        // `None` when it is used again by the loop around it
+        dropped: Box<Location>,
+        used: Option<Box<Location>>,
+    },
+    ReturnsLocalReference(Location),
+    ReferenceInStruct(Location),
 
+    BreakOutsideLoop(Location),
+    BreakOutsideLoopNoToken,
+    LeavesDefer(Location),
+    LeavesDeferNoToken,
+    ReturnOutsideFunction(Location),
+    ReturnOutsideFunctionNoToken,
+    MainReturnType(Location),
+    InvalidPanicHandler(Location),
+    DuplicatePanicHandler {
+        location: Location,
+        previous: Box<Location>,
+    },
+    Redefinition {
+        location: Location,
+        previous: Box<Location>,
+        // The name was brought in by a `use` before
+        imported: bool,
+        // The two have different names but are emitted under the same symbol
+        symbol: Option<Box<str>>,
+    },
+
+    UnresolvedImport(Location),
+    UnresolvedImportNoToken,
+    PrivateItem(Location),
+
+    UnknownAttribute(Location),
+    MalformedAttribute(Location),
+    MisplacedAttribute {
+        location: Location,
+        target: Target,
+    },
+    DuplicateAttribute {
+        location: Location,
+        previous: Box<Location>,
+        // The two have different arguments
+        conflicting: bool,
+    },
+    NotPrintable {
+        location: Location,
+        r#type: Box<Type>,
+        hex: bool,
+        // The format string the placeholder is in
+        format: Box<Location>,
+    },
+
+    // Each type is with what required it, when that is not `location` itself
     MismatchedType {
-        expected: Type,
-        actual: Type,
+        expected: Box<(Type, Option<Location>)>,
+        actual: Box<(Type, Option<Location>)>,
         location: Location,
     },
+
+    InvalidCast {
+        from: Box<Type>,
+        to: Box<Type>,
+        location: Location,
+    },
+    InvalidCastNoToken {
+        from: Box<Type>,
+        to: Box<Type>,
+    },
+
+    LiteralOutOfRange {
+        r#type: Box<Type>,
+        location: Location,
+    },
+    InvalidOperand {
+        location: Location,
+        r#type: Box<Type>,
+        operator: &'static str,
+    },
+    NegateUnsigned {
+        location: Location,
+        r#type: Box<Type>,
+    },
+
+    NoField {
+        r#type: Box<Type>,
+        index: usize,
+        location: Location,
+    },
+    NoFieldNoToken {
+        r#type: Box<Type>,
+        index: usize,
+    },
+    MoveOutOfField {
+        r#type: Box<Type>,
+        index: usize,
+        location: Location,
+    },
+    MoveOutOfFieldNoToken {
+        r#type: Box<Type>,
+        index: usize,
+    },
+    NoMethod {
+        r#type: Box<Type>,
+        name: Box<str>,
+        location: Location,
+    },
+    NoMethodNoToken {
+        r#type: Box<Type>,
+        name: Box<str>,
+    },
+    NotSliceable {
+        r#type: Box<Type>,
+        location: Location,
+    },
+    NotSliceableNoToken {
+        r#type: Box<Type>,
+    },
+    TupleArity {
+        expected: usize,
+        actual: Box<Type>,
+        location: Location,
+    },
+
+    ArgumentCount {
+        expected: usize,
+        actual: usize,
+        variadic: bool,
+        location: Location,
+    },
+    ArgumentCountNoToken {
+        expected: usize,
+        actual: usize,
+        variadic: bool,
+    },
 }
 
 impl std::fmt::Display for TypeCheckError {
@@ -84,6 +477,23 @@ impl std::fmt::Display for TypeCheckError {
                 Ok(())
             }
             TypeCheckError::UnsafeUseNoToken => unreachable!(),
+            TypeCheckError::UnsafeCall {
+                location,
+                declaration,
+            } => {
+                writeln!(
+                    f,
+                    "error: call to raw function `{}` outside of a raw function or `raw` block",
+                    declaration.value()
+                )?;
+                writeln!(f)?;
+                writeln!(f, "{}", location)?;
+                writeln!(f, "note: the function is declared `raw` here")?;
+                writeln!(f)?;
+                writeln!(f, "{}", declaration)?;
+                Ok(())
+            }
+            TypeCheckError::UnsafeCallNoToken { .. } => unreachable!(),
 
             TypeCheckError::UndeclaredFunction(location) => {
                 writeln!(f, "error: cannot find function `{}`", location.value())?;
@@ -92,21 +502,640 @@ impl std::fmt::Display for TypeCheckError {
                 Ok(())
             }
             TypeCheckError::UndeclaredFunctionNoToken => unreachable!(),
+            TypeCheckError::NotConstCall(location) => {
+                writeln!(
+                    f,
+                    "error: constants and `const fn`s can only call `const fn`s"
+                )?;
+                writeln!(f)?;
+                writeln!(f, "{}", location)?;
+                Ok(())
+            }
+            TypeCheckError::NotConstCallNoToken => unreachable!(),
+
+            TypeCheckError::UndeclaredVariable(location) => {
+                writeln!(
+                    f,
+                    "error: cannot find value `{}` in this scope",
+                    location.value()
+                )?;
+                writeln!(f)?;
+                writeln!(f, "{}", location)?;
+                Ok(())
+            }
+            TypeCheckError::UndeclaredVariableNoToken => unreachable!(),
+
+            TypeCheckError::UndeclaredType { name, location } => {
+                writeln!(f, "error: cannot find type `{}` in this scope", name)?;
+                writeln!(f)?;
+                writeln!(f, "{}", location)?;
+                Ok(())
+            }
+
+            TypeCheckError::UnknownTrait(location) => {
+                writeln!(
+                    f,
+                    "error: cannot find trait `{}` in this scope",
+                    location.value()
+                )?;
+                writeln!(f)?;
+                writeln!(f, "{}", location)?;
+                writeln!(f, "note: `Drop` is the only trait")?;
+                Ok(())
+            }
+            TypeCheckError::InvalidDrop(location) => {
+                writeln!(
+                    f,
+                    "error: `Drop` has to be implemented as `fn drop(value: T)` for its type `T`"
+                )?;
+                writeln!(f)?;
+                writeln!(f, "{}", location)?;
+                Ok(())
+            }
+
+            TypeCheckError::UseOfMovedValue { location, moved } => {
+                writeln!(f, "error: use of moved value `{}`", location.value())?;
+                writeln!(f)?;
+                writeln!(f, "{}", location)?;
+                match moved {
+                    Some(moved) => {
+                        writeln!(f, "note: value moved here")?;
+                        writeln!(f)?;
+                        writeln!(f, "{}", moved)?;
+                    }
+                    None => {
+                        writeln!(
+                            f,
+                            "note: value moved here, in the previous iteration of the loop"
+                        )?;
+                    }
+                }
+                Ok(())
+            }
+            TypeCheckError::MoveOutOfDrop(location) => {
+                writeln!(
+                    f,
+                    "error: cannot move out of `{}`, it is only lent to `drop`",
+                    location.value()
+                )?;
+                writeln!(f)?;
+                writeln!(f, "{}", location)?;
+                Ok(())
+            }
+
+            TypeCheckError::MoveOutOfReference(location) => {
+                writeln!(
+                    f,
+                    "error: cannot move out of `{}`, which is behind a reference or pointer",
+                    location.value()
+                )?;
+                writeln!(f)?;
+                writeln!(f, "{}", location)?;
+                Ok(())
+            }
+            TypeCheckError::MoveOutOfReferenceNoToken => unreachable!(),
+            TypeCheckError::MoveOutOfStatic(location) => {
+                writeln!(
+                    f,
+                    "error: cannot move out of `{}`, it is a `static`",
+                    location.value()
+                )?;
+                writeln!(f)?;
+                writeln!(f, "{}", location)?;
+                Ok(())
+            }
+
+            TypeCheckError::NotAPlace(location) => {
+                writeln!(
+                    f,
+                    "error: `{}` can not be borrowed or assigned to, it is not a binding, a field or a dereference",
+                    location.value()
+                )?;
+                writeln!(f)?;
+                writeln!(f, "{}", location)?;
+                Ok(())
+            }
+            TypeCheckError::ImmutablePlace(location) => {
+                writeln!(
+                    f,
+                    "error: cannot mutate `{}`, it is not declared with `let mut` or reached through a `&mut`",
+                    location.value()
+                )?;
+                writeln!(f)?;
+                writeln!(f, "{}", location)?;
+                Ok(())
+            }
+            TypeCheckError::BorrowConflict {
+                location,
+                loan,
+                access,
+            } => {
+                let value = location.value();
+                match access {
+                    Access::Read => writeln!(
+                        f,
+                        "error: cannot use `{}` because it is borrowed as mutable",
+                        value
+                    )?,
+                    Access::Move => writeln!(
+                        f,
+                        "error: cannot move out of `{}` because it is borrowed",
+                        value
+                    )?,
+                    Access::Write => writeln!(
+                        f,
+                        "error: cannot assign to `{}` because it is borrowed",
+                        value
+                    )?,
+                    Access::Borrow => writeln!(
+                        f,
+                        "error: cannot borrow `{}` as immutable because it is also borrowed as mutable",
+                        value
+                    )?,
+                    Access::BorrowMut => writeln!(
+                        f,
+                        "error: cannot borrow `{}` as mutable because it is also borrowed",
+                        value
+                    )?,
+                }
+                writeln!(f)?;
+                writeln!(f, "{}", location)?;
+                writeln!(f, "note: it is borrowed here")?;
+                writeln!(f)?;
+                writeln!(f, "{}", loan)?;
+                Ok(())
+            }
+            TypeCheckError::DoesNotLiveLongEnough {
+                location,
+                dropped,
+                used,
+            } => {
+                writeln!(f, "error: borrowed value does not live long enough")?;
+                writeln!(f)?;
+                writeln!(f, "{}", location)?;
+                writeln!(f, "note: the value goes out of scope here")?;
+                writeln!(f)?;
+                writeln!(f, "{}", dropped)?;
+                match used {
+                    Some(used) => {
+                        writeln!(f, "note: the reference is still used here")?;
+                        writeln!(f)?;
+                        writeln!(f, "{}", used)?;
+                    }
+                    None => writeln!(
+                        f,
+                        "note: the reference is used again in the next iteration of the loop"
+                    )?,
+                }
+                Ok(())
+            }
+            TypeCheckError::ReturnsLocalReference(location) => {
+                writeln!(
+                    f,
+                    "error: cannot return a reference to a value owned by the current function"
+                )?;
+                writeln!(f)?;
+                writeln!(f, "{}", location)?;
+                Ok(())
+            }
+            TypeCheckError::ReferenceInStruct(location) => {
+                writeln!(f, "error: struct fields can not be references")?;
+                writeln!(f)?;
+                writeln!(f, "{}", location)?;
+                Ok(())
+            }
+
+            TypeCheckError::BreakOutsideLoop(location) => {
+                writeln!(f, "error: `break` outside of a loop")?;
+                writeln!(f)?;
+                writeln!(f, "{}", location)?;
+                Ok(())
+            }
+            TypeCheckError::BreakOutsideLoopNoToken => unreachable!(),
+            TypeCheckError::LeavesDefer(location) => {
+                writeln!(
+                    f,
+                    "error: `break` and `return` can not leave a deferred block"
+                )?;
+                writeln!(f)?;
+                writeln!(f, "{}", location)?;
+                writeln!(
+                    f,
+                    "note: the block runs while its scope is being left, it has to finish"
+                )?;
+                Ok(())
+            }
+            TypeCheckError::LeavesDeferNoToken => unreachable!(),
+            TypeCheckError::ReturnOutsideFunction(location) => {
+                writeln!(f, "error: `return` outside of a function")?;
+                writeln!(f)?;
+                writeln!(f, "{}", location)?;
+                Ok(())
+            }
+            TypeCheckError::ReturnOutsideFunctionNoToken => unreachable!(),
+            TypeCheckError::MainReturnType(location) => {
+                writeln!(f, "error: `main` has to have the return type `!`")?;
+                writeln!(f)?;
+                writeln!(f, "{}", location)?;
+                Ok(())
+            }
+            TypeCheckError::InvalidPanicHandler(location) => {
+                writeln!(f, "error: the panic handler has the wrong signature")?;
+                writeln!(f)?;
+                writeln!(f, "{}", location)?;
+                writeln!(
+                    f,
+                    "note: it has to be `fn(message: str, file: str, line: u32, column: u32) -> !`"
+                )?;
+                Ok(())
+            }
+            TypeCheckError::DuplicatePanicHandler { location, previous } => {
+                writeln!(f, "error: there is more than one panic handler")?;
+                writeln!(f)?;
+                writeln!(f, "{}", location)?;
+                writeln!(f, "note: the first one is here")?;
+                writeln!(f)?;
+                writeln!(f, "{}", previous)?;
+                Ok(())
+            }
+            TypeCheckError::Redefinition {
+                location,
+                previous,
+                imported,
+                symbol,
+            } => {
+                match symbol {
+                    Some(symbol) => writeln!(
+                        f,
+                        "error: the symbol `{}` is defined more than once",
+                        symbol
+                    )?,
+                    None => writeln!(
+                        f,
+                        "error: the name `{}` is defined more than once",
+                        location.value()
+                    )?,
+                }
+                writeln!(f)?;
+                writeln!(f, "{}", location)?;
+                match imported {
+                    true => writeln!(f, "note: it is imported here")?,
+                    false => writeln!(f, "note: it is first defined here")?,
+                }
+                writeln!(f)?;
+                writeln!(f, "{}", previous)?;
+                Ok(())
+            }
+
+            TypeCheckError::UnresolvedImport(location) => {
+                writeln!(f, "error: unresolved import")?;
+                writeln!(f)?;
+                writeln!(f, "{}", location)?;
+                Ok(())
+            }
+            TypeCheckError::UnresolvedImportNoToken => unreachable!(),
+            TypeCheckError::PrivateItem(location) => {
+                writeln!(f, "error: function `{}` is private", location.value())?;
+                writeln!(f)?;
+                writeln!(f, "{}", location)?;
+                Ok(())
+            }
+
+            TypeCheckError::UnknownAttribute(location) => {
+                writeln!(
+                    f,
+                    "error: cannot find attribute `{}` in this scope",
+                    location.value()
+                )?;
+                writeln!(f)?;
+                writeln!(f, "{}", location)?;
+                Ok(())
+            }
+            TypeCheckError::MalformedAttribute(location) => {
+                writeln!(f, "error: malformed `{}` attribute input", location.value())?;
+                writeln!(f)?;
+                writeln!(f, "{}", location)?;
+                Ok(())
+            }
+            TypeCheckError::MisplacedAttribute { location, target } => {
+                writeln!(
+                    f,
+                    "error: `{}` can not be used on {}",
+                    location.value(),
+                    target
+                )?;
+                writeln!(f)?;
+                writeln!(f, "{}", location)?;
+                Ok(())
+            }
+            TypeCheckError::DuplicateAttribute {
+                location,
+                previous,
+                conflicting,
+            } => {
+                match conflicting {
+                    true => writeln!(f, "error: conflicting `{}` attributes", location.value())?,
+                    false => writeln!(
+                        f,
+                        "error: `{}` is used more than once on the item",
+                        location.value()
+                    )?,
+                }
+                writeln!(f)?;
+                writeln!(f, "{}", location)?;
+                writeln!(f, "note: the first one is here")?;
+                writeln!(f)?;
+                writeln!(f, "{}", previous)?;
+                Ok(())
+            }
+
+            TypeCheckError::NotPrintable {
+                location,
+                r#type,
+                hex,
+                format,
+            } => {
+                writeln!(
+                    f,
+                    "error: {} can not be printed with `{}`",
+                    r#type,
+                    if *hex { "{:x}" } else { "{}" }
+                )?;
+                writeln!(f)?;
+                writeln!(f, "{}", location)?;
+                writeln!(
+                    f,
+                    "note: `{{}}` prints integers and `str`, `{{:x}}` prints integers"
+                )?;
+                writeln!(f)?;
+                writeln!(f, "{}", format)?;
+                Ok(())
+            }
 
             TypeCheckError::MismatchedType {
                 expected,
-                actual: found,
+                actual,
                 location,
             } => {
                 writeln!(
                     f,
                     "error: mismatched types, expected {}, found {}",
-                    expected, found
+                    expected.0, actual.0
+                )?;
+                writeln!(f)?;
+                writeln!(f, "{}", location)?;
+                for (t, origin) in [&**expected, &**actual] {
+                    if let Some(origin) = origin {
+                        writeln!(f, "note: {} is required by this", t)?;
+                        writeln!(f)?;
+                        writeln!(f, "{}", origin)?;
+                    }
+                }
+                Ok(())
+            }
+
+            TypeCheckError::InvalidCast { from, to, location } => {
+                writeln!(f, "error: casting {} as {} is invalid", from, to)?;
+                writeln!(f)?;
+                writeln!(f, "{}", location)?;
+                Ok(())
+            }
+            TypeCheckError::InvalidCastNoToken { .. } => unreachable!(),
+
+            TypeCheckError::LiteralOutOfRange { r#type, location } => {
+                writeln!(f, "error: literal out of range for {}", r#type)?;
+                writeln!(f)?;
+                writeln!(f, "{}", location)?;
+                Ok(())
+            }
+            TypeCheckError::InvalidOperand {
+                location,
+                r#type,
+                operator,
+            } => {
+                writeln!(f, "error: can not apply `{}` to {}", operator, r#type)?;
+                writeln!(f)?;
+                writeln!(f, "{}", location)?;
+                writeln!(f, "note: arithmetic only works on integers")?;
+                Ok(())
+            }
+            TypeCheckError::NegateUnsigned { location, r#type } => {
+                writeln!(f, "error: can not negate a value of type {}", r#type)?;
+                writeln!(f)?;
+                writeln!(f, "{}", location)?;
+                writeln!(f, "note: unsigned values can not be negative")?;
+                Ok(())
+            }
+
+            TypeCheckError::NoField {
+                r#type,
+                index,
+                location,
+            } => {
+                writeln!(f, "error: no field `{}` on type {}", index, r#type)?;
+                writeln!(f)?;
+                writeln!(f, "{}", location)?;
+                Ok(())
+            }
+            TypeCheckError::NoFieldNoToken { .. } => unreachable!(),
+            TypeCheckError::MoveOutOfField {
+                r#type,
+                index,
+                location,
+            } => {
+                writeln!(
+                    f,
+                    "error: cannot move out of field `{}` of {}",
+                    index, r#type
+                )?;
+                writeln!(f)?;
+                writeln!(f, "{}", location)?;
+                Ok(())
+            }
+            TypeCheckError::MoveOutOfFieldNoToken { .. } => unreachable!(),
+            TypeCheckError::NoMethod {
+                r#type,
+                name,
+                location,
+            } => {
+                writeln!(f, "error: no method named `{}` found for {}", name, r#type)?;
+                writeln!(f)?;
+                writeln!(f, "{}", location)?;
+                Ok(())
+            }
+            TypeCheckError::NoMethodNoToken { .. } => unreachable!(),
+            TypeCheckError::NotSliceable { r#type, location } => {
+                writeln!(f, "error: cannot slice a value of type {}", r#type)?;
+                writeln!(f)?;
+                writeln!(f, "{}", location)?;
+                Ok(())
+            }
+            TypeCheckError::NotSliceableNoToken { .. } => unreachable!(),
+            TypeCheckError::TupleArity {
+                expected,
+                actual,
+                location,
+            } => {
+                writeln!(
+                    f,
+                    "error: mismatched types, expected a tuple with {} element{}, found {}",
+                    expected,
+                    if *expected == 1 { "" } else { "s" },
+                    actual
+                )?;
+                writeln!(f)?;
+                writeln!(f, "{}", location)?;
+                Ok(())
+            }
+
+            TypeCheckError::ArgumentCount {
+                expected,
+                actual,
+                variadic,
+                location,
+            } => {
+                writeln!(
+                    f,
+                    "error: this function takes {}{} argument{} but {} {} supplied",
+                    if *variadic { "at least " } else { "" },
+                    expected,
+                    if *expected == 1 { "" } else { "s" },
+                    actual,
+                    if *actual == 1 {
+                        "argument was"
+                    } else {
+                        "arguments were"
+                    },
+                )?;
+                writeln!(f)?;
+                writeln!(f, "{}", location)?;
+                Ok(())
+            }
+            TypeCheckError::ArgumentCountNoToken { .. } => unreachable!(),
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum EvaluationError {
+    NotConst(Location),
+    NotConstNoToken,
+    OutOfBounds {
+        length: usize,
+        location: Location,
+    },
+    OutOfBoundsNoToken {
+        length: usize,
+    },
+    Drop(Location),
+    Cycle(Location),
+    StepLimit(Location),
+    RecursionLimit(Location),
+    RecursionLimitNoToken,
+    Panic {
+        message: Box<str>,
+        location: Location,
+    },
+    PanicNoToken {
+        message: Box<str>,
+    },
+}
+
+impl std::fmt::Display for EvaluationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EvaluationError::NotConst(location) => {
+                writeln!(
+                    f,
+                    "error: `{}` can not be evaluated at compile time",
+                    location.value()
+                )?;
+                writeln!(f)?;
+                writeln!(f, "{}", location)?;
+                Ok(())
+            }
+            EvaluationError::NotConstNoToken => unreachable!(),
+            EvaluationError::OutOfBounds { length, location } => {
+                writeln!(
+                    f,
+                    "error: range out of bounds for a slice of length {}",
+                    length
+                )?;
+                writeln!(f)?;
+                writeln!(f, "{}", location)?;
+                Ok(())
+            }
+            EvaluationError::OutOfBoundsNoToken { .. } => unreachable!(),
+            EvaluationError::Drop(location) => {
+                writeln!(
+                    f,
+                    "error: the value bound here would be dropped, which can not happen at compile time"
+                )?;
+                writeln!(f)?;
+                writeln!(f, "{}", location)?;
+                Ok(())
+            }
+            EvaluationError::Cycle(location) => {
+                writeln!(
+                    f,
+                    "error: the value of `{}` depends on itself",
+                    location.value()
                 )?;
                 writeln!(f)?;
                 writeln!(f, "{}", location)?;
                 Ok(())
             }
+            EvaluationError::StepLimit(location) => {
+                writeln!(f, "error: compile-time evaluation takes too long")?;
+                writeln!(f)?;
+                writeln!(f, "{}", location)?;
+                writeln!(
+                    f,
+                    "note: a constant has to be worked out within {} steps",
+                    crate::evaluator::STEPS
+                )?;
+                Ok(())
+            }
+            EvaluationError::RecursionLimit(location) => {
+                writeln!(f, "error: compile-time evaluation recurses too deeply")?;
+                writeln!(f)?;
+                writeln!(f, "{}", location)?;
+                writeln!(
+                    f,
+                    "note: calls can only be nested {} deep",
+                    crate::evaluator::DEPTH
+                )?;
+                Ok(())
+            }
+            EvaluationError::RecursionLimitNoToken => unreachable!(),
+            EvaluationError::Panic { message, location } => {
+                writeln!(f, "error: {}", message)?;
+                writeln!(f)?;
+                writeln!(f, "{}", location)?;
+                writeln!(f, "note: this is evaluated at compile time")?;
+                Ok(())
+            }
+            EvaluationError::PanicNoToken { .. } => unreachable!(),
+        }
+    }
+}
+
+// Problems that do not stop the compilation, they are reported once checking is done
+#[derive(Debug)]
+pub enum Warning {
+    UnusedRaw(Location),
+}
+
+impl std::fmt::Display for Warning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Warning::UnusedRaw(location) => {
+                writeln!(f, "warning: unnecessary `raw` block")?;
+                writeln!(f)?;
+                writeln!(f, "{}", location)?;
+                writeln!(f, "note: nothing in it is an unsafe operation")?;
+                Ok(())
+            }
         }
     }
 }
@@ -0,0 +1,223 @@
+// The values of `const` and `static` items are worked out before any code is generated, by
+// walking their checked initializers and the `const fn`s those call. Each item gets at most
+// `STEPS` statements and `DEPTH` nested calls, so a constant that never finishes is an error
+
+use std::{collections::HashMap, rc::Rc};
+
+use crate::{
+    error::EvaluationError,
+    instruction::{
+        Instruction, block::Block, const_declaration::ConstDeclaration,
+        function_declaration::FunctionDeclaration,
+    },
+    ir_generator::resolve_integers,
+    lexer::{IntType, Location, Type},
+    parser::{Statement, StatementKind},
+    type_checker::{StructSignature, needs_drop},
+};
+
+pub const STEPS: usize = 1_000_000;
+pub const DEPTH: usize = 256;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    // Integers, `bool` and `char` by their bits, as wide as their type
+    Int(u128),
+    // The bytes of a `str` or `[u8]`
+    Bytes(Vec<u8>),
+    // The elements of a tuple or the fields of a struct
+    Tuple(Vec<Value>),
+}
+
+impl Value {
+    pub const UNIT: Self = Self::Tuple(Vec::new());
+}
+
+// Why the evaluation of an expression stopped before it had a value
+#[derive(Debug)]
+pub enum Interrupt {
+    Break,
+    Return(Value),
+    Error(EvaluationError),
+}
+
+impl From<EvaluationError> for Interrupt {
+    fn from(error: EvaluationError) -> Self {
+        Interrupt::Error(error)
+    }
+}
+
+pub struct Evaluator {
+    // The `const fn`s by the name they are called by
+    functions: HashMap<String, Rc<FunctionDeclaration>>,
+    // The `const` and `static` items by their key, in the order they are declared
+    items: HashMap<String, Rc<ConstDeclaration>>,
+    order: Vec<String>,
+    // The values worked out so far and the items being worked out, innermost last
+    values: HashMap<String, Value>,
+    pending: Vec<String>,
+    // The bindings of each call being evaluated, by the index the type checker gave them
+    frames: Vec<HashMap<usize, Value>>,
+    steps: usize,
+
+    structs: HashMap<String, StructSignature>,
+    integers: Vec<Type>,
+    // `debug_assert` is left out of release builds
+    pub release: bool,
+}
+
+impl Evaluator {
+    pub fn new(
+        ast: &Statement,
+        structs: HashMap<String, StructSignature>,
+        integers: Vec<Type>,
+    ) -> Self {
+        let mut evaluator = Self {
+            functions: HashMap::new(),
+            items: HashMap::new(),
+            order: Vec::new(),
+            values: HashMap::new(),
+            pending: Vec::new(),
+            frames: Vec::new(),
+            steps: 0,
+
+            structs,
+            integers,
+            release: false,
+        };
+        evaluator.collect(ast);
+        evaluator
+    }
+
+    // Finds the items in the module and in the bodies of functions and blocks
+    fn collect(&mut self, statement: &Statement) {
+        match &statement.kind {
+            StatementKind::Module { ast: body, .. } | StatementKind::Block(Block { body, .. }) => {
+                for statement in body {
+                    self.collect(statement);
+                }
+            }
+            StatementKind::FunctionDeclaration(function) => self.collect_function(function),
+            StatementKind::Impl(r#impl) => {
+                for function in &r#impl.functions {
+                    self.collect_function(function);
+                }
+            }
+            StatementKind::ConstDeclaration(item) => {
+                self.order.push(item.key.clone());
+                self.items.insert(item.key.clone(), Rc::new(item.clone()));
+                self.collect(&item.value);
+            }
+            StatementKind::Loop(r#loop) => self.collect(&r#loop.body),
+            StatementKind::RawBlock(raw_block) => self.collect(&raw_block.body),
+            _ => (),
+        }
+    }
+
+    fn collect_function(&mut self, function: &FunctionDeclaration) {
+        if function.constant {
+            self.functions
+                .insert(function.key(), Rc::new(function.clone()));
+        }
+        self.collect(&function.body);
+    }
+
+    // Works out every item, each gets the full number of steps
+    pub fn run(mut self) -> Result<HashMap<String, Value>, EvaluationError> {
+        for name in self.order.clone() {
+            self.steps = 0;
+            let location = self.items[&name].location.clone();
+            self.constant(&name, &location)?;
+        }
+        Ok(self.values)
+    }
+
+    // The value of an item, worked out the first time it is needed
+    pub fn constant(&mut self, name: &str, location: &Location) -> Result<Value, EvaluationError> {
+        if let Some(value) = self.values.get(name) {
+            return Ok(value.clone());
+        }
+        if self.pending.iter().any(|pending| pending == name) {
+            return Err(EvaluationError::Cycle(location.clone()));
+        }
+        let Some(item) = self.items.get(name).cloned() else {
+            return Err(EvaluationError::NotConst(location.clone()));
+        };
+        self.pending.push(name.to_string());
+        self.frame()?;
+        let value = match item.value.evaluate(self) {
+            Ok(value) | Err(Interrupt::Return(value)) => value,
+            Err(Interrupt::Break) => unreachable!(),
+            Err(Interrupt::Error(error)) => return Err(error),
+        };
+        self.frames.pop();
+        self.pending.pop();
+        self.values.insert(name.to_string(), value.clone());
+        Ok(value)
+    }
+
+    // Runs a `const fn` with the values of its arguments
+    pub fn call(&mut self, name: &str, arguments: Vec<Value>) -> Result<Value, Interrupt> {
+        let Some(function) = self.functions.get(name).cloned() else {
+            return Err(EvaluationError::NotConstNoToken.into());
+        };
+        self.frame()?;
+        for (local, argument) in arguments.into_iter().enumerate() {
+            self.set_variable(local, argument);
+        }
+        let value = function.evaluate_call(self);
+        self.frames.pop();
+        value
+    }
+
+    fn frame(&mut self) -> Result<(), EvaluationError> {
+        if self.frames.len() >= DEPTH {
+            return Err(EvaluationError::RecursionLimitNoToken);
+        }
+        self.frames.push(HashMap::new());
+        Ok(())
+    }
+
+    pub fn step(&mut self, location: &Location) -> Result<(), EvaluationError> {
+        self.steps += 1;
+        if self.steps > STEPS {
+            return Err(EvaluationError::StepLimit(location.clone()));
+        }
+        Ok(())
+    }
+
+    pub fn variable(&self, local: usize) -> Value {
+        self.frames.last().unwrap()[&local].clone()
+    }
+
+    pub fn variable_mut(&mut self, local: usize) -> &mut Value {
+        self.frames.last_mut().unwrap().get_mut(&local).unwrap()
+    }
+
+    pub fn set_variable(&mut self, local: usize, value: Value) {
+        self.frames.last_mut().unwrap().insert(local, value);
+    }
+
+    pub fn resolve(&self, t: &Type) -> Type {
+        resolve_integers(&self.integers, t)
+    }
+
+    pub fn needs_drop(&self, t: &Type) -> bool {
+        needs_drop(&self.structs, &self.resolve(t))
+    }
+}
+
+// The value of the bits of an integer of the type
+pub fn to_i128(bits: u128, int: IntType) -> i128 {
+    let shift = 128 - int.bits();
+    match int.signed() {
+        true => ((bits << shift) as i128) >> shift,
+        false => bits as i128,
+    }
+}
+
+// The bits of a value cut off to the type and whether it did not fit
+pub fn from_i128(value: i128, int: IntType) -> (u128, bool) {
+    let bits = value as u128 & (u128::MAX >> (128 - int.bits()));
+    (bits, to_i128(bits, int) != value)
+}
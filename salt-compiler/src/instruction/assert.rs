@@ -0,0 +1,89 @@
+use crate::{
+    error::{EvaluationError, ExpectedToken, ParseError, TypeCheckError},
+    evaluator::{Evaluator, Interrupt, Value},
+    ir_generator::IrGenerator,
+    lexer::{Location, Token, TokenKind, Type},
+    parser::{Parser, Statement},
+    type_checker::TypeChecker,
+};
+
+use super::Instruction;
+
+#[derive(Debug, Clone)]
+pub struct Assert {
+    pub condition: Box<Statement>,
+    // What it panics with, made from the source of the condition and the message if there is one
+    message: String,
+    location: Location,
+    // A `debug_assert` is left out of release builds
+    debug: bool,
+}
+
+impl Instruction for Assert {
+    // `assert(condition)` or `assert(condition, "message")`
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        let debug = parser.expect_ident()? == "debug_assert";
+        let start = parser.previous().location.clone();
+        parser.expect(&TokenKind::OpenParen)?;
+        let first = parser.position();
+        let condition = Box::new(Statement::parse_expression(parser)?);
+        let mut message = format!("assertion failed: {}", parser.source_text(first));
+        if let Some(TokenKind::Comma) = parser.peek().map(|token| token.kind) {
+            parser.bump();
+            match parser.bump() {
+                Some(Token {
+                    kind: TokenKind::StringLiteral(text),
+                    ..
+                }) => message = format!("{message}: {text}"),
+                Some(token) => {
+                    return Err(ParseError::UnexpectedToken {
+                        actual: Box::new(token),
+                        expected: ExpectedToken::Specific {
+                            kind: TokenKind::StringLiteral(String::new()),
+                        },
+                    });
+                }
+                None => return Err(ParseError::UnexpectedEOF),
+            }
+        }
+        parser.expect(&TokenKind::CloseParen)?;
+        Ok(Self {
+            condition,
+            message,
+            location: start.to(&parser.previous().location),
+            debug,
+        })
+    }
+
+    // The condition is checked in every build, even if it is left out of it
+    fn check(&mut self, type_checker: &mut TypeChecker) -> Result<Type, TypeCheckError> {
+        let t = self.condition.check(type_checker)?;
+        type_checker.unify(&Type::Bool, &t, &self.condition.location, None)?;
+        Ok(Type::UNIT)
+    }
+
+    fn gen_ir(&self, ir_generator: &mut IrGenerator) {
+        if !(self.debug && ir_generator.release) {
+            self.condition.gen_ir(ir_generator);
+            let condition = ir_generator.pop_value();
+            let failed = ir_generator.new_value();
+            ir_generator.emit(&format!("%{} = xor i1 {}, true", failed, condition));
+            ir_generator.panic_if(&format!("%{}", failed), &self.message, &self.location);
+        }
+        ir_generator.value = "zeroinitializer".to_string();
+    }
+
+    fn evaluate(&self, evaluator: &mut Evaluator) -> Result<Value, Interrupt> {
+        if self.debug && evaluator.release {
+            return Ok(Value::UNIT);
+        }
+        match self.condition.evaluate(evaluator)? {
+            Value::Int(0) => Err(EvaluationError::Panic {
+                message: self.message.as_str().into(),
+                location: self.location.clone(),
+            }
+            .into()),
+            _ => Ok(Value::UNIT),
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use crate::{
+    error::{EvaluationError, ParseError, TypeCheckError},
+    evaluator::{Evaluator, Interrupt, Value},
+    ir_generator::IrGenerator,
+    lexer::{TokenKind, Type},
+    parser::{Parser, Statement, StatementKind},
+    place,
+    type_checker::{Access, TypeChecker},
+};
+
+use super::Instruction;
+
+#[derive(Debug, Clone)]
+pub struct Assignment {
+    pub place: Box<Statement>,
+    value: Box<Statement>,
+    r#type: Type,
+    // The place still holds a value that has to be dropped before it is overwritten
+    drop: bool,
+}
+
+impl Instruction for Assignment {
+    // Parses the `= value` after a place, the caller fills in the place it belongs to
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        parser.expect(&TokenKind::Equals)?;
+        let value = Box::new(Statement::parse_expression(parser)?);
+        Ok(Self {
+            place: Box::new(Statement::EMPTY),
+            value,
+            r#type: Type::UNIT,
+            drop: false,
+        })
+    }
+
+    // The value is computed before the place is looked at, so it can use what the place holds
+    fn check(&mut self, type_checker: &mut TypeChecker) -> Result<Type, TypeCheckError> {
+        let mark = type_checker.mark();
+        let t = self.value.check(type_checker)?;
+        let loans = type_checker.provenance(&mark);
+        type_checker.finish_expression(&mark);
+
+        // Assigning to a binding that was moved out of gives it a value again
+        let (place, owned) = match &mut self.place.kind {
+            StatementKind::Variable(variable) => variable.check_assignee(type_checker)?,
+            _ => (place::check_place(&mut self.place, type_checker)?, true),
+        };
+        type_checker.unify(
+            &place.r#type,
+            &t,
+            &self.value.location,
+            Some(&self.place.location),
+        )?;
+        if !place.mutable {
+            return Err(TypeCheckError::ImmutablePlace(self.place.location.clone()));
+        }
+        self.r#type = place.r#type;
+        self.drop = owned && type_checker.needs_drop(&self.r#type);
+        if let Some(root) = place.root {
+            type_checker.access(root, Access::Write, &place.location)?;
+            if let StatementKind::Variable(_) = self.place.kind {
+                type_checker.reinitialize(root);
+            }
+            type_checker.derive(root, loans);
+        }
+        Ok(Type::UNIT)
+    }
+
+    fn gen_ir(&self, ir_generator: &mut IrGenerator) {
+        self.value.gen_ir(ir_generator);
+        let value = ir_generator.pop_value();
+        let address = place::gen_address(&self.place, ir_generator);
+        let t = ir_generator.resolve(&self.r#type);
+        if t == Type::Never {
+            return;
+        }
+        if self.drop {
+            let old = ir_generator.new_value();
+            ir_generator.emit(&format!(
+                "%{} = load {}, {}* {}",
+                old,
+                t.to_ir(),
+                t.to_ir(),
+                address
+            ));
+            ir_generator.drop_value(&format!("%{}", old), &t);
+        }
+        ir_generator.emit(&format!(
+            "store {} {}, {}* {}",
+            t.to_ir(),
+            value,
+            t.to_ir(),
+            address
+        ));
+        ir_generator.value = "zeroinitializer".to_string();
+    }
+
+    // Only bindings and the fields of them can be assigned to, there are no references at
+    // compile time
+    fn evaluate(&self, evaluator: &mut Evaluator) -> Result<Value, Interrupt> {
+        let value = self.value.evaluate(evaluator)?;
+        let mut path = Vec::new();
+        let mut place = &*self.place;
+        let local = loop {
+            match &place.kind {
+                StatementKind::Variable(variable) => match variable.local {
+                    Some(local) => break local,
+                    None => return Err(EvaluationError::NotConstNoToken.into()),
+                },
+                StatementKind::Field(field) if !field.deref => {
+                    path.push(field.index);
+                    place = &field.value;
+                }
+                _ => return Err(EvaluationError::NotConstNoToken.into()),
+            }
+        };
+        let mut target = evaluator.variable_mut(local);
+        for index in path.into_iter().rev() {
+            let Value::Tuple(elements) = target else {
+                unreachable!()
+            };
+            target = &mut elements[index];
+        }
+        *target = value;
+        Ok(Value::UNIT)
+    }
+}
@@ -0,0 +1,192 @@
+use crate::{
+    error::{ExpectedToken, ParseError, TypeCheckError},
+    ir_generator::IrGenerator,
+    lexer::{Location, Token, TokenKind, Type},
+    parser::Parser,
+    type_checker::TypeChecker,
+};
+
+use super::Instruction;
+
+#[derive(Debug, Clone)]
+pub struct Attribute {
+    pub name: String,
+    // `#[name(a, "b")]`, `#[name = "value"]` is the same as `#[name("value")]`
+    pub arguments: Vec<Argument>,
+    pub location: Location,
+    // The kind of item the attribute is on, the item fills it in
+    pub target: Target,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Argument {
+    Identifier(String),
+    String(String),
+}
+
+// The kinds of items that can have attributes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Target {
+    Function,
+    ExternFunction,
+    Struct,
+    Const,
+    Static,
+}
+
+impl std::fmt::Display for Target {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Target::Function => write!(f, "a function"),
+            Target::ExternFunction => write!(f, "an extern function"),
+            Target::Struct => write!(f, "a struct"),
+            Target::Const => write!(f, "a `const`"),
+            Target::Static => write!(f, "a `static`"),
+        }
+    }
+}
+
+impl Instruction for Attribute {
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        parser.expect(&TokenKind::Hash)?;
+        parser.expect(&TokenKind::OpenBracket)?;
+        let location = match parser.peek() {
+            Some(token) => token.location,
+            None => return Err(ParseError::UnexpectedEOF),
+        };
+        let name = parser.expect_ident()?;
+
+        let mut arguments = Vec::new();
+        match parser.peek().map(|token| token.kind) {
+            Some(TokenKind::Equals) => {
+                parser.bump();
+                match parser.bump() {
+                    Some(Token {
+                        kind: TokenKind::StringLiteral(value),
+                        ..
+                    }) => arguments.push(Argument::String(value)),
+                    Some(token) => {
+                        return Err(ParseError::UnexpectedToken {
+                            actual: Box::new(token),
+                            expected: ExpectedToken::Specific {
+                                kind: TokenKind::StringLiteral(String::new()),
+                            },
+                        });
+                    }
+                    None => return Err(ParseError::UnexpectedEOF),
+                }
+            }
+            Some(TokenKind::OpenParen) => {
+                parser.bump();
+                while let Some(token) = parser.peek()
+                    && token.kind != TokenKind::CloseParen
+                {
+                    parser.bump();
+                    arguments.push(match token.kind {
+                        TokenKind::Identifier(name) => Argument::Identifier(name),
+                        TokenKind::StringLiteral(value) => Argument::String(value),
+                        _ => {
+                            return Err(ParseError::UnexpectedToken {
+                                actual: Box::new(token),
+                                expected: ExpectedToken::AttributeArgument,
+                            });
+                        }
+                    });
+                    if let Some(Token {
+                        kind: TokenKind::Comma,
+                        ..
+                    }) = parser.peek()
+                    {
+                        parser.bump();
+                    } else {
+                        break;
+                    }
+                }
+                parser.expect(&TokenKind::CloseParen)?;
+            }
+            _ => (),
+        }
+        parser.expect(&TokenKind::CloseBracket)?;
+
+        Ok(Self {
+            name,
+            arguments,
+            location,
+            target: Target::Function,
+        })
+    }
+
+    // Every attribute is only valid on some kinds of items and takes its own arguments
+    fn check(&mut self, _type_checker: &mut TypeChecker) -> Result<Type, TypeCheckError> {
+        let (targets, valid): (&[Target], bool) = match self.name.as_str() {
+            "link_name" => (&[Target::ExternFunction], self.string().is_some()),
+            "no_mangle" => (
+                &[Target::Function, Target::Static],
+                self.arguments.is_empty(),
+            ),
+            "section" => (&[Target::Function, Target::Static], self.string().is_some()),
+            "inline" => (&[Target::Function], self.inline().is_some()),
+            "cold" => (&[Target::Function], self.arguments.is_empty()),
+            "panic_handler" => (&[Target::Function], self.arguments.is_empty()),
+            _ => return Err(TypeCheckError::UnknownAttribute(self.location.clone())),
+        };
+        if !targets.contains(&self.target) {
+            return Err(TypeCheckError::MisplacedAttribute {
+                location: self.location.clone(),
+                target: self.target,
+            });
+        }
+        if !valid {
+            return Err(TypeCheckError::MalformedAttribute(self.location.clone()));
+        }
+        Ok(Type::UNIT)
+    }
+
+    // Attributes change how the item they are attached to is emitted
+    fn gen_ir(&self, _ir_generator: &mut IrGenerator) {}
+}
+
+impl Attribute {
+    // The argument of an attribute that takes a single string
+    pub fn string(&self) -> Option<&str> {
+        match self.arguments.as_slice() {
+            [Argument::String(value)] => Some(value),
+            _ => None,
+        }
+    }
+
+    // The LLVM function attribute of `#[inline]`, `#[inline(always)]` or `#[inline(never)]`
+    pub fn inline(&self) -> Option<&'static str> {
+        match self.arguments.as_slice() {
+            [] => Some("inlinehint"),
+            [Argument::Identifier(hint)] if hint == "always" => Some("alwaysinline"),
+            [Argument::Identifier(hint)] if hint == "never" => Some("noinline"),
+            _ => None,
+        }
+    }
+}
+
+// Checks the attributes of an item of the given kind, each can only be given once
+pub fn check_attributes(
+    attributes: &mut [Attribute],
+    target: Target,
+    type_checker: &mut TypeChecker,
+) -> Result<(), TypeCheckError> {
+    for i in 0..attributes.len() {
+        attributes[i].target = target;
+        attributes[i].check(type_checker)?;
+        if let Some(previous) = find(&attributes[..i], &attributes[i].name) {
+            return Err(TypeCheckError::DuplicateAttribute {
+                location: attributes[i].location.clone(),
+                previous: Box::new(previous.location.clone()),
+                conflicting: previous.arguments != attributes[i].arguments,
+            });
+        }
+    }
+    Ok(())
+}
+
+// The first attribute with the name
+pub fn find<'a>(attributes: &'a [Attribute], name: &str) -> Option<&'a Attribute> {
+    attributes.iter().find(|attribute| attribute.name == name)
+}
@@ -0,0 +1,285 @@
+use crate::{
+    error::{EvaluationError, ParseError, TypeCheckError},
+    evaluator::{self, Evaluator, Interrupt, Value},
+    ir_generator::IrGenerator,
+    lexer::{IntType, Location, TokenKind, Type},
+    parser::{Parser, Statement},
+    type_checker::TypeChecker,
+};
+
+use super::Instruction;
+
+#[derive(Debug, Clone)]
+pub struct Binary {
+    pub operator: Operator,
+    pub left: Box<Statement>,
+    pub right: Box<Statement>,
+    // The whole expression, which is where it panics
+    pub location: Location,
+    r#type: Type,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Operator {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+}
+
+impl Operator {
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Operator::Add => "+",
+            Operator::Sub => "-",
+            Operator::Mul => "*",
+            Operator::Div => "/",
+            Operator::Rem => "%",
+        }
+    }
+
+    // What a panic because the result does not fit says
+    pub fn overflow_message(self) -> &'static str {
+        match self {
+            Operator::Add => "attempt to add with overflow",
+            Operator::Sub => "attempt to subtract with overflow",
+            Operator::Mul => "attempt to multiply with overflow",
+            Operator::Div => "attempt to divide with overflow",
+            Operator::Rem => "attempt to calculate the remainder with overflow",
+        }
+    }
+
+    fn zero_message(self) -> &'static str {
+        match self {
+            Operator::Rem => "attempt to calculate the remainder with a divisor of zero",
+            _ => "attempt to divide by zero",
+        }
+    }
+
+    // The wrapped result for integers of type `int` and whether it overflowed, `None` when
+    // dividing by zero
+    pub fn apply(self, int: IntType, left: u128, right: u128) -> Option<(u128, bool)> {
+        let (a, b) = (
+            evaluator::to_i128(left, int),
+            evaluator::to_i128(right, int),
+        );
+        let result = match self {
+            Operator::Add => a.checked_add(b),
+            Operator::Sub => a.checked_sub(b),
+            Operator::Mul => a.checked_mul(b),
+            Operator::Div if b == 0 => return None,
+            Operator::Div => a.checked_div(b),
+            Operator::Rem if b == 0 => return None,
+            Operator::Rem => a.checked_rem(b),
+        };
+        Some(match result {
+            Some(value) => evaluator::from_i128(value, int),
+            // Only the product of two 64 bit integers goes past `i128`, its low bits are right
+            None => (
+                evaluator::from_i128(left.wrapping_mul(right) as i128, int).0,
+                true,
+            ),
+        })
+    }
+}
+
+impl Instruction for Binary {
+    // Parses the operator and the operand after it, the caller fills in the one before it
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        let operator = match parser.bump().map(|token| token.kind) {
+            Some(TokenKind::Plus) => Operator::Add,
+            Some(TokenKind::Minus) => Operator::Sub,
+            Some(TokenKind::Star) => Operator::Mul,
+            Some(TokenKind::Slash) => Operator::Div,
+            Some(TokenKind::Percent) => Operator::Rem,
+            _ => unreachable!(),
+        };
+        let right = match operator {
+            Operator::Add | Operator::Sub => Statement::parse_product(parser)?,
+            _ => Statement::parse_cast(parser)?,
+        };
+        Ok(Self {
+            operator,
+            left: Box::new(Statement::EMPTY),
+            right: Box::new(right),
+            location: Location::default(),
+            r#type: Type::UNIT,
+        })
+    }
+
+    // Both operands are integers of the same type, which is the type of the result
+    fn check(&mut self, type_checker: &mut TypeChecker) -> Result<Type, TypeCheckError> {
+        let left = self.left.check(type_checker)?;
+        let right = self.right.check(type_checker)?;
+        for (t, operand) in [(&left, &self.left), (&right, &self.right)] {
+            if !matches!(
+                type_checker.resolve(t),
+                Type::Int(_) | Type::IntVar(_) | Type::Never
+            ) {
+                return Err(TypeCheckError::InvalidOperand {
+                    location: operand.location.clone(),
+                    r#type: Box::new(t.clone()),
+                    operator: self.operator.symbol(),
+                });
+            }
+        }
+        type_checker.unify(
+            &left,
+            &right,
+            &self.right.location,
+            Some(&self.left.location),
+        )?;
+        self.r#type = if left == Type::Never { right } else { left };
+        Ok(self.r#type.clone())
+    }
+
+    // Debug builds check every operation, release builds wrap, except when dividing since LLVM
+    // leaves dividing by zero and `MIN / -1` undefined
+    fn gen_ir(&self, ir_generator: &mut IrGenerator) {
+        self.left.gen_ir(ir_generator);
+        let left = ir_generator.pop_value();
+        self.right.gen_ir(ir_generator);
+        let right = ir_generator.pop_value();
+        let Type::Int(int) = ir_generator.resolve(&self.r#type) else {
+            ir_generator.value = "undef".to_string();
+            return;
+        };
+        let t = Type::Int(int).to_ir();
+        let sign = if int.signed() { "s" } else { "u" };
+
+        let instruction = match self.operator {
+            Operator::Add | Operator::Sub | Operator::Mul if ir_generator.release => {
+                self.operator.llvm_name().to_string()
+            }
+            Operator::Add | Operator::Sub | Operator::Mul => {
+                let operation = format!("{}{}", sign, self.operator.llvm_name());
+                ir_generator.value = checked(
+                    ir_generator,
+                    &operation,
+                    int,
+                    &left,
+                    &right,
+                    self.operator.overflow_message(),
+                    &self.location,
+                );
+                return;
+            }
+            Operator::Div | Operator::Rem => {
+                let zero = ir_generator.new_value();
+                ir_generator.emit(&format!("%{} = icmp eq {} {}, 0", zero, t, right));
+                ir_generator.panic_if(
+                    &format!("%{}", zero),
+                    self.operator.zero_message(),
+                    &self.location,
+                );
+                if int.signed() {
+                    let min = ir_generator.new_value();
+                    ir_generator.emit(&format!("%{} = icmp eq {} {}, {}", min, t, left, int.min()));
+                    let minus_one = ir_generator.new_value();
+                    ir_generator.emit(&format!("%{} = icmp eq {} {}, -1", minus_one, t, right));
+                    let overflowed = ir_generator.new_value();
+                    ir_generator.emit(&format!(
+                        "%{} = and i1 %{}, %{}",
+                        overflowed, min, minus_one
+                    ));
+                    ir_generator.panic_if(
+                        &format!("%{}", overflowed),
+                        self.operator.overflow_message(),
+                        &self.location,
+                    );
+                }
+                format!("{}{}", sign, self.operator.llvm_name())
+            }
+        };
+        let result = ir_generator.new_value();
+        ir_generator.emit(&format!(
+            "%{} = {} {} {}, {}",
+            result, instruction, t, left, right
+        ));
+        ir_generator.value = format!("%{}", result);
+    }
+
+    // A constant that would panic is an error instead
+    fn evaluate(&self, evaluator: &mut Evaluator) -> Result<Value, Interrupt> {
+        let left = self.left.evaluate(evaluator)?;
+        let right = self.right.evaluate(evaluator)?;
+        let (Type::Int(int), Value::Int(left), Value::Int(right)) =
+            (evaluator.resolve(&self.r#type), left, right)
+        else {
+            unreachable!()
+        };
+        match self.operator.apply(int, left, right) {
+            Some((value, false)) => Ok(Value::Int(value)),
+            Some((_, true)) => Err(EvaluationError::PanicNoToken {
+                message: self.operator.overflow_message().into(),
+            }
+            .into()),
+            None => Err(EvaluationError::PanicNoToken {
+                message: self.operator.zero_message().into(),
+            }
+            .into()),
+        }
+    }
+}
+
+impl Operator {
+    // The name of the LLVM instruction, which `div` and `rem` put the sign in front of
+    fn llvm_name(self) -> &'static str {
+        match self {
+            Operator::Add => "add",
+            Operator::Sub => "sub",
+            Operator::Mul => "mul",
+            Operator::Div => "div",
+            Operator::Rem => "rem",
+        }
+    }
+}
+
+// Calls `@llvm.{operation}.with.overflow` and returns the `{ iN, i1 }` of the wrapped result and
+// whether it overflowed
+pub fn with_overflow(
+    ir_generator: &mut IrGenerator,
+    operation: &str,
+    int: IntType,
+    left: &str,
+    right: &str,
+) -> String {
+    let t = Type::Int(int).to_ir();
+    let function = format!("llvm.{}.with.overflow.{}", operation, t);
+    ir_generator.declare_symbol(
+        &function,
+        &format!("declare {{ {t}, i1 }} @{function}({t}, {t})\n"),
+    );
+    let pair = ir_generator.new_value();
+    ir_generator.emit(&format!(
+        "%{} = call {{ {t}, i1 }} @{}({t} {}, {t} {})",
+        pair, function, left, right
+    ));
+    format!("%{}", pair)
+}
+
+// The wrapped result of an operation that is checked for overflow, it panics if it overflowed
+pub fn checked(
+    ir_generator: &mut IrGenerator,
+    operation: &str,
+    int: IntType,
+    left: &str,
+    right: &str,
+    message: &str,
+    location: &Location,
+) -> String {
+    let pair = with_overflow(ir_generator, operation, int, left, right);
+    let pair_type = Type::Tuple(vec![Type::Int(int), Type::Bool]).to_ir();
+    let mut parts = Vec::new();
+    for i in 0..2 {
+        let part = ir_generator.new_value();
+        ir_generator.emit(&format!(
+            "%{} = extractvalue {} {}, {}",
+            part, pair_type, pair, i
+        ));
+        parts.push(format!("%{}", part));
+    }
+    ir_generator.panic_if(&parts[1], message, location);
+    parts.swap_remove(0)
+}
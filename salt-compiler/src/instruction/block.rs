@@ -1,20 +1,31 @@
 use anyhow::Result;
 
 use crate::{
-    error::{ParseError, TypeCheckError},
+    error::{EvaluationError, ParseError, TypeCheckError},
+    evaluator::{Evaluator, Interrupt, Value},
     ir_generator::IrGenerator,
-    lexer::{TokenKind, Type},
-    parser::{Parser, Statement},
-    type_checker::TypeChecker,
+    lexer::{Location, TokenKind, Type},
+    parser::{Parser, Statement, StatementKind},
+    type_checker::{Cleanup, TypeChecker},
 };
 
+use super::Instruction;
+
 #[derive(Debug, Clone)]
 pub struct Block {
     pub body: Vec<Statement>,
     returns: bool,
+    // The closing brace, where the bindings go out of scope
+    end: Location,
+    // The body of a function, its value is what the function returns
+    pub function_body: bool,
+    // The type of each statement, values nothing takes are dropped right away
+    types: Vec<Type>,
+    // The deferred blocks and the bindings that still own a value at its end
+    cleanup: Vec<Cleanup>,
 }
 
-impl super::Instruction for Block {
+impl Instruction for Block {
     fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
         parser.bump();
         let mut body = Vec::new();
@@ -23,37 +34,108 @@ impl super::Instruction for Block {
         {
             body.push(Statement::parse(parser)?);
         }
+        parser.expect(&TokenKind::CloseBrace)?;
+        let end = parser.previous().location.clone();
 
-        let returns = parser.block_returns();
+        let returns = body.last().is_some_and(|statement| statement.returns);
 
-        Ok(Self { body, returns })
+        Ok(Self {
+            body,
+            returns,
+            end,
+            function_body: false,
+            types: Vec::new(),
+            cleanup: Vec::new(),
+        })
     }
 
-    fn check(&self, type_checker: &mut TypeChecker) -> Result<Type, TypeCheckError> {
+    // A block never finishes if one of its statements does not
+    fn check(&mut self, type_checker: &mut TypeChecker) -> Result<Type, TypeCheckError> {
         let mut last_type = Type::Any;
-        for statement in &self.body {
+        let mut diverges = false;
+        type_checker.new_scope();
+        self.types.clear();
+        let tail = self.body.len().wrapping_sub(1);
+        for (i, statement) in self.body.iter_mut().enumerate() {
+            let mark = type_checker.mark();
             last_type = statement.check(type_checker)?;
+            diverges |= last_type == Type::Never;
+            self.types.push(last_type.clone());
+            // The references in the value of the block live on after it
+            if self.returns && i == tail && last_type.contains_reference() {
+                let loans = type_checker.provenance(&mark);
+                if self.function_body {
+                    type_checker.check_return(&loans)?;
+                }
+                type_checker.finish_expression(&mark);
+                type_checker.escape(loans);
+            } else {
+                type_checker.finish_expression(&mark);
+            }
         }
-        if self.returns {
+        let value = self
+            .body
+            .last()
+            .map_or(&self.end, |statement| &statement.location);
+        self.cleanup = type_checker.finish_scope(&self.end, value)?;
+        if diverges {
+            Ok(Type::Never)
+        } else if self.returns {
             Ok(last_type)
         } else {
-            Ok(Type::Void)
+            Ok(Type::UNIT)
         }
     }
 
     fn gen_ir(&self, ir_generator: &mut IrGenerator) {
-        let mut ir = String::new();
-        for statement in &self.body {
+        let outer = ir_generator.pop_stash();
+        let mut value = String::new();
+        for (i, (statement, t)) in self.body.iter().zip(&self.types).enumerate() {
             statement.gen_ir(ir_generator);
-            ir.push_str(&ir_generator.pop_stash());
-            ir.push('\n');
+            value = ir_generator.pop_value();
+            if !(self.returns && i == self.body.len() - 1) {
+                ir_generator.drop_value(&value, t);
+            }
         }
+        ir_generator.clean_up(&self.cleanup);
+        let ir = ir_generator.pop_stash();
 
-        ir_generator.stash = ir
-            .split("\n")
-            .map(|e| String::from("  ") + e)
-            .collect::<Vec<_>>()
-            .join("\n")
-            + "\n";
+        ir_generator.stash = outer
+            + &ir
+                .lines()
+                .map(|e| String::from("  ") + e + "\n")
+                .collect::<String>();
+        ir_generator.value = match self.returns {
+            true => value,
+            false => "zeroinitializer".to_string(),
+        };
+    }
+
+    // Drops run code that is not `const`, so a block can only be evaluated without any. The
+    // `let` of a binding that would be dropped is what can not be evaluated
+    fn evaluate(&self, evaluator: &mut Evaluator) -> Result<Value, Interrupt> {
+        if let Some(Cleanup::Drop(local, _)) = self.cleanup.first() {
+            let declaration = self.body.iter().find(|statement| match &statement.kind {
+                StatementKind::Let(r#let) => r#let.locals().contains(local),
+                _ => false,
+            });
+            return Err(match declaration {
+                Some(declaration) => EvaluationError::Drop(declaration.location.clone()),
+                None => EvaluationError::NotConstNoToken,
+            }
+            .into());
+        }
+        let mut value = Value::UNIT;
+        for (i, (statement, t)) in self.body.iter().zip(&self.types).enumerate() {
+            value = statement.evaluate(evaluator)?;
+            if !(self.returns && i == self.body.len() - 1) && evaluator.needs_drop(t) {
+                return Err(EvaluationError::NotConst(statement.location.clone()).into());
+            }
+        }
+        if self.returns {
+            Ok(value)
+        } else {
+            Ok(Value::UNIT)
+        }
     }
 }
@@ -0,0 +1,41 @@
+use crate::{
+    error::{EvaluationError, ParseError, TypeCheckError},
+    evaluator::{Evaluator, Interrupt, Value},
+    ir_generator::IrGenerator,
+    lexer::Type,
+    parser::Parser,
+    type_checker::{Cleanup, TypeChecker},
+};
+
+#[derive(Debug, Clone)]
+pub struct Break {
+    // Everything in the loop that has to be cleaned up
+    cleanup: Vec<Cleanup>,
+}
+
+impl super::Instruction for Break {
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        parser.bump();
+        Ok(Self {
+            cleanup: Vec::new(),
+        })
+    }
+
+    fn check(&mut self, type_checker: &mut TypeChecker) -> Result<Type, TypeCheckError> {
+        self.cleanup = type_checker.new_break()?;
+        Ok(Type::Never)
+    }
+
+    fn gen_ir(&self, ir_generator: &mut IrGenerator) {
+        ir_generator.clean_up(&self.cleanup);
+        let loop_nr = *ir_generator.loops.last().unwrap();
+        ir_generator.unreachable(&format!("br label %loop_exit{}", loop_nr));
+    }
+
+    fn evaluate(&self, _evaluator: &mut Evaluator) -> Result<Value, Interrupt> {
+        if !self.cleanup.is_empty() {
+            return Err(EvaluationError::NotConstNoToken.into());
+        }
+        Err(Interrupt::Break)
+    }
+}
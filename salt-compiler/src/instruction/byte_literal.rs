@@ -0,0 +1,35 @@
+use crate::{
+    error::{ParseError, TypeCheckError},
+    evaluator::{Evaluator, Interrupt, Value},
+    ir_generator::IrGenerator,
+    lexer::{IntType, TokenKind, Type},
+    parser::Parser,
+    type_checker::TypeChecker,
+};
+
+#[derive(Debug, Clone)]
+pub struct ByteLiteral {
+    value: u8,
+}
+
+impl super::Instruction for ByteLiteral {
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        if let TokenKind::ByteLiteral(value) = parser.bump().unwrap().kind {
+            Ok(Self { value })
+        } else {
+            unreachable!()
+        }
+    }
+
+    fn check(&mut self, _type_checker: &mut TypeChecker) -> Result<Type, TypeCheckError> {
+        Ok(Type::Int(IntType::U8))
+    }
+
+    fn gen_ir(&self, ir_generator: &mut IrGenerator) {
+        ir_generator.value = self.value.to_string();
+    }
+
+    fn evaluate(&self, _evaluator: &mut Evaluator) -> Result<Value, Interrupt> {
+        Ok(Value::Int(self.value as u128))
+    }
+}
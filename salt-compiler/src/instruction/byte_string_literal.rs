@@ -0,0 +1,35 @@
+use crate::{
+    error::{ParseError, TypeCheckError},
+    evaluator::{Evaluator, Interrupt, Value},
+    ir_generator::IrGenerator,
+    lexer::{TokenKind, Type},
+    parser::Parser,
+    type_checker::TypeChecker,
+};
+
+#[derive(Debug, Clone)]
+pub struct ByteStringLiteral {
+    value: Vec<u8>,
+}
+
+impl super::Instruction for ByteStringLiteral {
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        if let TokenKind::ByteStringLiteral(value) = parser.bump().unwrap().kind {
+            Ok(Self { value })
+        } else {
+            unreachable!()
+        }
+    }
+
+    fn check(&mut self, _type_checker: &mut TypeChecker) -> Result<Type, TypeCheckError> {
+        Ok(Type::ByteStr)
+    }
+
+    fn gen_ir(&self, ir_generator: &mut IrGenerator) {
+        ir_generator.value = ir_generator.new_slice(&self.value);
+    }
+
+    fn evaluate(&self, _evaluator: &mut Evaluator) -> Result<Value, Interrupt> {
+        Ok(Value::Bytes(self.value.clone()))
+    }
+}
@@ -0,0 +1,33 @@
+use crate::{
+    error::{ParseError, TypeCheckError},
+    ir_generator::IrGenerator,
+    lexer::{IntType, TokenKind, Type},
+    parser::Parser,
+    type_checker::TypeChecker,
+};
+
+#[derive(Debug, Clone)]
+pub struct CStringLiteral {
+    value: Vec<u8>,
+}
+
+impl super::Instruction for CStringLiteral {
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        if let TokenKind::CStringLiteral(value) = parser.bump().unwrap().kind {
+            Ok(Self { value })
+        } else {
+            unreachable!()
+        }
+    }
+
+    fn check(&mut self, _type_checker: &mut TypeChecker) -> Result<Type, TypeCheckError> {
+        Ok(Type::Pointer {
+            mutable: false,
+            pointee: Box::new(Type::Int(IntType::U8)),
+        })
+    }
+
+    fn gen_ir(&self, ir_generator: &mut IrGenerator) {
+        ir_generator.value = ir_generator.new_string(&self.value);
+    }
+}
@@ -0,0 +1,174 @@
+use crate::{
+    error::{EvaluationError, ParseError, TypeCheckError},
+    evaluator::{Evaluator, Interrupt, Value},
+    ir_generator::IrGenerator,
+    lexer::{IntType, Keyword, TokenKind, Type},
+    parser::{Parser, Statement},
+    type_checker::TypeChecker,
+};
+
+use super::Instruction;
+
+#[derive(Debug, Clone)]
+pub struct Cast {
+    pub value: Box<Statement>,
+    pub to: Type,
+    from: Type,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CastKind {
+    // The value is already represented the same way
+    Noop,
+    Trunc,
+    Zext,
+    Sext,
+    IntToPtr,
+    PtrToInt,
+    Bitcast,
+}
+
+impl CastKind {
+    // Integer types with the bits they are stored in and whether they are signed
+    fn integer(t: &Type) -> Option<(u32, bool)> {
+        match t {
+            Type::Bool => Some((1, false)),
+            Type::Char => Some((32, false)),
+            Type::Int(int) => Some((int.bits(), int.signed())),
+            _ => None,
+        }
+    }
+
+    fn new(from: &Type, to: &Type) -> Option<Self> {
+        if from == to {
+            return Some(CastKind::Noop);
+        }
+        match (from, to) {
+            (Type::Pointer { .. }, Type::Int(_)) => Some(CastKind::PtrToInt),
+            // Any integer can become a narrower or wider one, the value of a signed source is kept
+            (_, Type::Int(int)) => {
+                let (bits, signed) = Self::integer(from)?;
+                Some(match bits.cmp(&int.bits()) {
+                    std::cmp::Ordering::Equal => CastKind::Noop,
+                    std::cmp::Ordering::Greater => CastKind::Trunc,
+                    std::cmp::Ordering::Less if signed => CastKind::Sext,
+                    std::cmp::Ordering::Less => CastKind::Zext,
+                })
+            }
+            // Only bytes are guaranteed to be valid codepoints
+            (Type::Int(IntType::U8), Type::Char) => Some(CastKind::Zext),
+            (Type::Int(_), Type::Pointer { .. }) => Some(CastKind::IntToPtr),
+            (Type::Pointer { .. }, Type::Pointer { .. }) => Some(CastKind::Bitcast),
+            // A reference can always be turned into a pointer, but not one that mutates more
+            (
+                Type::Reference {
+                    mutable: from_mutable,
+                    ..
+                },
+                Type::Pointer { mutable, .. },
+            ) if *from_mutable || !mutable => Some(CastKind::Bitcast),
+            _ => None,
+        }
+    }
+
+    // Casts between pointers and integers conjure up addresses, so they need a raw function
+    fn raw(self) -> bool {
+        matches!(self, CastKind::IntToPtr | CastKind::PtrToInt)
+    }
+
+    fn to_ir(self) -> &'static str {
+        match self {
+            CastKind::Noop => unreachable!(),
+            CastKind::Trunc => "trunc",
+            CastKind::Zext => "zext",
+            CastKind::Sext => "sext",
+            CastKind::IntToPtr => "inttoptr",
+            CastKind::PtrToInt => "ptrtoint",
+            CastKind::Bitcast => "bitcast",
+        }
+    }
+}
+
+impl Instruction for Cast {
+    // Parses the `as T` after a value, the caller fills in the value it belongs to
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        parser.expect(&TokenKind::Keyword(Keyword::As))?;
+        let to = parser.expect_type()?;
+        Ok(Self {
+            value: Box::new(Statement::EMPTY),
+            to,
+            from: Type::UNIT,
+        })
+    }
+
+    fn check(&mut self, type_checker: &mut TypeChecker) -> Result<Type, TypeCheckError> {
+        // An integer with nothing else deciding its type takes the fallback before it is converted
+        let from = self.value.check(type_checker)?;
+        self.to = type_checker.resolve_type(&self.to);
+        self.from = match self.to {
+            Type::Pointer { .. } => type_checker.default_address(&from, &self.value.location),
+            _ => type_checker.default_integer(&from, &self.value.location),
+        };
+        match CastKind::new(&self.from, &self.to) {
+            Some(kind) if kind.raw() && !type_checker.allows_unsafe() => {
+                Err(TypeCheckError::UnsafeUseNoToken)
+            }
+            Some(_) => Ok(self.to.clone()),
+            None => Err(TypeCheckError::InvalidCastNoToken {
+                from: Box::new(self.from.clone()),
+                to: Box::new(self.to.clone()),
+            }),
+        }
+    }
+
+    fn gen_ir(&self, ir_generator: &mut IrGenerator) {
+        self.value.gen_ir(ir_generator);
+        let kind = CastKind::new(&self.from, &self.to).unwrap();
+        if kind == CastKind::Noop {
+            return;
+        }
+        let mut value = ir_generator.pop_value();
+        let mut from = ir_generator.resolve(&self.from);
+        // An address is 64 bits, narrower integers are extended by their sign first
+        if kind == CastKind::IntToPtr
+            && let Type::Int(int) = from
+            && int.bits() < 64
+        {
+            let extended = ir_generator.new_value();
+            ir_generator.emit(&format!(
+                "%{} = {} {} {} to i64",
+                extended,
+                if int.signed() { "sext" } else { "zext" },
+                from.to_ir(),
+                value
+            ));
+            value = format!("%{}", extended);
+            from = Type::Int(IntType::I64);
+        }
+        let result = ir_generator.new_value();
+        ir_generator.emit(&format!(
+            "%{} = {} {} {} to {}",
+            result,
+            kind.to_ir(),
+            from.to_ir(),
+            value,
+            self.to.to_ir()
+        ));
+        ir_generator.value = format!("%{}", result);
+    }
+
+    // Integers are kept as their bits, so a cast cuts them off or extends them
+    fn evaluate(&self, evaluator: &mut Evaluator) -> Result<Value, Interrupt> {
+        let value = self.value.evaluate(evaluator)?;
+        let from = evaluator.resolve(&self.from);
+        let (Some((from_bits, signed)), Some((to_bits, _)), Value::Int(mut bits)) =
+            (CastKind::integer(&from), CastKind::integer(&self.to), value)
+        else {
+            return Err(EvaluationError::NotConstNoToken.into());
+        };
+        if signed && bits >> (from_bits - 1) & 1 == 1 {
+            bits |= u128::MAX << from_bits;
+        }
+        Ok(Value::Int(bits & (u128::MAX >> (128 - to_bits))))
+    }
+}
@@ -0,0 +1,35 @@
+use crate::{
+    error::{ParseError, TypeCheckError},
+    evaluator::{Evaluator, Interrupt, Value},
+    ir_generator::IrGenerator,
+    lexer::{TokenKind, Type},
+    parser::Parser,
+    type_checker::TypeChecker,
+};
+
+#[derive(Debug, Clone)]
+pub struct CharLiteral {
+    value: char,
+}
+
+impl super::Instruction for CharLiteral {
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        if let TokenKind::CharLiteral(value) = parser.bump().unwrap().kind {
+            Ok(Self { value })
+        } else {
+            unreachable!()
+        }
+    }
+
+    fn check(&mut self, _type_checker: &mut TypeChecker) -> Result<Type, TypeCheckError> {
+        Ok(Type::Char)
+    }
+
+    fn gen_ir(&self, ir_generator: &mut IrGenerator) {
+        ir_generator.value = (self.value as u32).to_string();
+    }
+
+    fn evaluate(&self, _evaluator: &mut Evaluator) -> Result<Value, Interrupt> {
+        Ok(Value::Int(self.value as u128))
+    }
+}
@@ -0,0 +1,132 @@
+use crate::{
+    error::{ParseError, TypeCheckError},
+    ir_generator::IrGenerator,
+    lexer::{Keyword, Location, Token, TokenKind, Type},
+    parser::{Parser, Statement},
+    type_checker::{ConstantSignature, TypeChecker},
+};
+
+use super::{
+    Instruction,
+    attribute::{self, Attribute, Target},
+};
+
+#[derive(Debug, Clone)]
+pub struct ConstDeclaration {
+    pub name: String,
+    // The name the type checker knows it by, see `TypeChecker::constant_key`
+    pub key: String,
+    pub location: Location,
+    pub doc: Vec<String>,
+    pub attributes: Vec<Attribute>,
+    pub public: bool,
+    // A `static` is one value in memory, a `const` is put in everywhere it is used
+    pub r#static: bool,
+    pub r#type: Type,
+    // The written type
+    type_location: Location,
+    pub value: Box<Statement>,
+}
+
+impl Instruction for ConstDeclaration {
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        let doc = parser.doc_comments();
+        let attributes = parser.expect_attributes()?;
+        let public = parser.expect_visibility();
+        let r#static = matches!(
+            parser.bump(),
+            Some(Token {
+                kind: TokenKind::Keyword(Keyword::Static),
+                ..
+            })
+        );
+        let name = parser.expect_ident()?;
+        let location = parser.previous().location.clone();
+        parser.expect(&TokenKind::Colon)?;
+        let start = parser.peek().ok_or(ParseError::UnexpectedEOF)?.location;
+        let r#type = parser.expect_type()?;
+        let type_location = start.to(&parser.previous().location);
+        parser.expect(&TokenKind::Equals)?;
+        let value = Box::new(Statement::parse_expression(parser)?);
+        parser.expect(&TokenKind::Semicolon)?;
+
+        Ok(Self {
+            key: name.clone(),
+            name,
+            location,
+            doc,
+            attributes,
+            public,
+            r#static,
+            r#type,
+            type_location,
+            value,
+        })
+    }
+
+    // The value is checked like the body of a `const fn`, the evaluator works it out later
+    fn check(&mut self, type_checker: &mut TypeChecker) -> Result<Type, TypeCheckError> {
+        let target = match self.r#static {
+            true => Target::Static,
+            false => Target::Const,
+        };
+        attribute::check_attributes(&mut self.attributes, target, type_checker)?;
+        type_checker.check_type(&mut self.r#type, &self.type_location)?;
+        self.key = type_checker.constant_key(&self.name).unwrap().clone();
+        type_checker.new_constant(&self.name, &self.r#type, &self.type_location);
+        let t = self.value.check(type_checker)?;
+        type_checker.unify(
+            &self.r#type,
+            &t,
+            &self.value.location,
+            Some(&self.type_location),
+        )?;
+        type_checker.finish_function()?;
+        Ok(Type::UNIT)
+    }
+
+    // Only a `static` exists in the module, a `const` is generated where it is used
+    fn gen_ir(&self, ir_generator: &mut IrGenerator) {
+        if !self.r#static {
+            return;
+        }
+        let linkage = if ir_generator.values.is_empty() && (self.public || self.no_mangle()) {
+            ""
+        } else {
+            "internal "
+        };
+        let value = ir_generator.evaluated[&self.key].clone();
+        let mut ir = String::new();
+        for line in &self.doc {
+            ir.push_str(&format!(";{line}\n"));
+        }
+        let initializer = ir_generator.constant(&value, &self.r#type);
+        ir.push_str(&format!(
+            "@{} = {}constant {} {}{}\n",
+            ir_generator.constants[&self.key].symbol,
+            linkage,
+            self.r#type.to_ir(),
+            initializer,
+            match attribute::find(&self.attributes, "section").and_then(Attribute::string) {
+                Some(section) => format!(", section \"{}\"", section),
+                None => String::new(),
+            }
+        ));
+        ir_generator.function_declarations.push(ir);
+    }
+}
+
+impl ConstDeclaration {
+    // A `#[no_mangle]` static is emitted under its own name
+    pub fn no_mangle(&self) -> bool {
+        attribute::find(&self.attributes, "no_mangle").is_some()
+    }
+
+    pub fn signature(&self, symbol: String) -> ConstantSignature {
+        ConstantSignature {
+            symbol,
+            r#type: self.r#type.clone(),
+            r#static: self.r#static,
+        }
+    }
+}
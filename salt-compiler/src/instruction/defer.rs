@@ -0,0 +1,41 @@
+use crate::{
+    error::{ParseError, TypeCheckError},
+    ir_generator::IrGenerator,
+    lexer::Type,
+    parser::{Parser, Statement},
+    type_checker::TypeChecker,
+};
+
+#[derive(Debug, Clone)]
+pub struct Defer {
+    pub body: Box<Statement>,
+    // The index the type checker gave the deferred block
+    index: usize,
+}
+
+impl super::Instruction for Defer {
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        parser.bump();
+        let body = Box::new(Statement::parse_expression(parser)?);
+        Ok(Self { body, index: 0 })
+    }
+
+    // The body is checked where it is written, so it can only use what is declared before it
+    fn check(&mut self, type_checker: &mut TypeChecker) -> Result<Type, TypeCheckError> {
+        type_checker.new_defer();
+        let t = self.body.check(type_checker)?;
+        type_checker.unify(&Type::UNIT, &t, &self.body.last().location, None)?;
+        self.index = type_checker.finish_defer();
+        Ok(Type::UNIT)
+    }
+
+    // Nothing runs here, the body is generated at every exit of the scope
+    fn gen_ir(&self, ir_generator: &mut IrGenerator) {
+        ir_generator
+            .defers
+            .last_mut()
+            .unwrap()
+            .insert(self.index, (*self.body).clone());
+        ir_generator.value = "zeroinitializer".to_string();
+    }
+}
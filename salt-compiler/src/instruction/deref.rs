@@ -0,0 +1,99 @@
+use crate::{
+    error::{ParseError, TypeCheckError},
+    ir_generator::IrGenerator,
+    lexer::{Location, Type},
+    parser::{Parser, Statement},
+    place::{self, Place},
+    type_checker::{Access, TypeChecker},
+};
+
+use super::Instruction;
+
+#[derive(Debug, Clone)]
+pub struct Deref {
+    pub value: Box<Statement>,
+    location: Location,
+    // The type of what the value points to
+    r#type: Type,
+}
+
+impl Instruction for Deref {
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        parser.bump();
+        let start = parser.previous().location.clone();
+        let value = Box::new(Statement::parse_unary(parser)?);
+        Ok(Self {
+            value,
+            location: start.to(&parser.previous().location),
+            r#type: Type::UNIT,
+        })
+    }
+
+    // Reading through a reference leaves what it points to where it is, so it has to be `Copy`
+    fn check(&mut self, type_checker: &mut TypeChecker) -> Result<Type, TypeCheckError> {
+        let place = self.check_place(type_checker)?;
+        if let Some(root) = place.root {
+            type_checker.access(root, Access::Read, &place.location)?;
+        }
+        if !type_checker.resolve(&place.r#type).is_copy() {
+            return Err(TypeCheckError::MoveOutOfReferenceNoToken);
+        }
+        Ok(place.r#type)
+    }
+
+    fn gen_ir(&self, ir_generator: &mut IrGenerator) {
+        let address = self.gen_address(ir_generator);
+        let t = ir_generator.resolve(&self.r#type).to_ir();
+        let result = ir_generator.new_value();
+        ir_generator.emit(&format!("%{} = load {}, {}* {}", result, t, t, address));
+        ir_generator.value = format!("%{}", result);
+    }
+}
+
+impl Deref {
+    // What a reference points to can be used freely, what a pointer points to only in a raw
+    // function
+    pub fn check_place(&mut self, type_checker: &mut TypeChecker) -> Result<Place, TypeCheckError> {
+        let (t, root, location) = if place::is_place(&self.value, type_checker) {
+            let place = place::check_place(&mut self.value, type_checker)?;
+            (place.r#type, place.root, place.location)
+        } else {
+            let t = self.value.check(type_checker)?;
+            (t, None, self.value.location.clone())
+        };
+        let (mutable, t) = match type_checker.resolve(&t) {
+            Type::Reference { mutable, referent } => (mutable, *referent),
+            Type::Pointer { mutable, pointee } if type_checker.allows_unsafe() => {
+                (mutable, *pointee)
+            }
+            Type::Pointer { .. } => return Err(TypeCheckError::UnsafeUse(self.location.clone())),
+            t => {
+                return Err(TypeCheckError::MismatchedType {
+                    expected: Box::new((
+                        Type::Reference {
+                            mutable: false,
+                            referent: Box::new(Type::Any),
+                        },
+                        None,
+                    )),
+                    actual: Box::new((t, None)),
+                    location: self.value.location.clone(),
+                });
+            }
+        };
+        self.r#type = t.clone();
+        Ok(Place {
+            r#type: t,
+            root,
+            through_deref: true,
+            mutable,
+            location,
+        })
+    }
+
+    // The address is the value of the reference
+    pub fn gen_address(&self, ir_generator: &mut IrGenerator) -> String {
+        self.value.gen_ir(ir_generator);
+        ir_generator.pop_value()
+    }
+}
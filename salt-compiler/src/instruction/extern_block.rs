@@ -0,0 +1,155 @@
+use crate::{
+    error::{ExpectedToken, ParseError, TypeCheckError},
+    ir_generator::IrGenerator,
+    lexer::{Keyword, Location, Token, TokenKind, Type},
+    parser::Parser,
+    type_checker::{FunctionSignature, TypeChecker},
+};
+
+use super::{
+    Instruction,
+    attribute::{self, Attribute, Target},
+    function_declaration::Parameter,
+};
+
+#[derive(Debug, Clone)]
+pub struct ExternBlock {
+    pub functions: Vec<ExternFunction>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ExternFunction {
+    pub name: String,
+    pub location: Location,
+    pub doc: Vec<String>,
+    pub parameters: Vec<Parameter>,
+    pub variadic: bool,
+    pub return_type: Type,
+    pub attributes: Vec<Attribute>,
+
+    pub public: bool,
+    // What the function does is not checked, so calls to it need a raw context
+    pub raw: bool,
+}
+
+impl Instruction for ExternBlock {
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        parser.bump();
+        // Only the C ABI is supported, `extern { ... }` defaults to it
+        match parser.peek() {
+            Some(Token {
+                kind: TokenKind::StringLiteral(abi),
+                ..
+            }) if abi == "C" => {
+                parser.bump();
+            }
+            Some(
+                token @ Token {
+                    kind: TokenKind::StringLiteral(_),
+                    ..
+                },
+            ) => {
+                return Err(ParseError::UnexpectedToken {
+                    actual: Box::new(token),
+                    expected: ExpectedToken::Abi,
+                });
+            }
+            _ => (),
+        }
+
+        parser.expect(&TokenKind::OpenBrace)?;
+        let mut functions = Vec::new();
+        while let Some(token) = parser.peek()
+            && token.kind != TokenKind::CloseBrace
+        {
+            functions.push(ExternFunction::parse(parser)?);
+        }
+        parser.expect(&TokenKind::CloseBrace)?;
+
+        Ok(Self { functions })
+    }
+
+    fn check(&mut self, type_checker: &mut TypeChecker) -> Result<Type, TypeCheckError> {
+        for function in &mut self.functions {
+            attribute::check_attributes(
+                &mut function.attributes,
+                Target::ExternFunction,
+                type_checker,
+            )?;
+        }
+        Ok(Type::UNIT)
+    }
+
+    // The same function can be declared by several blocks, LLVM only takes one declaration
+    fn gen_ir(&self, ir_generator: &mut IrGenerator) {
+        for function in &self.functions {
+            let signature = function.signature();
+            let mut ir = String::new();
+            for line in &function.doc {
+                ir.push_str(&format!(";{line}\n"));
+            }
+            ir.push_str(&signature.declare_ir());
+            ir_generator.declare_symbol(&signature.symbol, &ir);
+        }
+    }
+}
+
+impl ExternFunction {
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        let doc = parser.doc_comments();
+        let attributes = parser.expect_attributes()?;
+        let public = parser.expect_visibility();
+        let raw = match parser.peek() {
+            Some(Token {
+                kind: TokenKind::Keyword(Keyword::Raw),
+                ..
+            }) => {
+                parser.bump();
+                true
+            }
+            _ => false,
+        };
+        parser.expect(&TokenKind::Keyword(Keyword::Fn))?;
+        let name = parser.expect_ident()?;
+        let location = parser.previous().location.clone();
+        let (parameters, variadic) = parser.expect_parameters(true)?;
+        let return_type = parser.expect_optional_type()?;
+        parser.expect(&TokenKind::Semicolon)?;
+
+        Ok(Self {
+            name,
+            location,
+            doc,
+            parameters,
+            variadic,
+            return_type,
+            attributes,
+
+            public,
+            raw,
+        })
+    }
+
+    pub fn link_name(&self) -> &str {
+        attribute::find(&self.attributes, "link_name")
+            .and_then(Attribute::string)
+            .unwrap_or(&self.name)
+    }
+
+    pub fn signature(&self) -> FunctionSignature {
+        FunctionSignature {
+            symbol: self.link_name().to_string(),
+            parameters: self
+                .parameters
+                .iter()
+                .map(|parameter| parameter.r#type.clone())
+                .collect(),
+            variadic: self.variadic,
+            return_type: self.return_type.clone(),
+            c_abi: true,
+            constant: false,
+            raw: self.raw,
+            location: self.location.clone(),
+        }
+    }
+}
@@ -0,0 +1,190 @@
+use crate::{
+    error::{EvaluationError, ExpectedToken, ParseError, TypeCheckError},
+    evaluator::{Evaluator, Interrupt, Value},
+    ir_generator::IrGenerator,
+    lexer::{Token, TokenKind, Type},
+    parser::{Parser, Statement},
+    place::{self, Place},
+    type_checker::{Access, TypeChecker},
+};
+
+use super::Instruction;
+
+#[derive(Debug, Clone)]
+pub struct Field {
+    pub value: Box<Statement>,
+    pub index: usize,
+    // The tuple or struct the field is in and the type of the field
+    tuple: Type,
+    r#type: Type,
+    // The value is a reference to the tuple or struct
+    pub deref: bool,
+    // The value is a place, so the field is read from memory rather than taken out of the value
+    place: bool,
+    // The value is a temporary that has to be dropped once the field is read
+    drop: bool,
+}
+
+impl Instruction for Field {
+    // Parses the `.0` after a value, the caller fills in the value it belongs to
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        parser.expect(&TokenKind::Dot)?;
+        let index = match parser.bump() {
+            Some(Token {
+                kind: TokenKind::IntegerLiteral((index, None)),
+                ..
+            }) => index as usize,
+            Some(token) => Err(ParseError::UnexpectedToken {
+                actual: Box::new(token),
+                expected: ExpectedToken::Field,
+            })?,
+            None => Err(ParseError::UnexpectedEOF)?,
+        };
+        Ok(Self {
+            value: Box::new(Statement::EMPTY),
+            index,
+            tuple: Type::UNIT,
+            r#type: Type::UNIT,
+            deref: false,
+            place: false,
+            drop: false,
+        })
+    }
+
+    // Reading a field leaves the value it is in as it is, so only `Copy` fields can be read
+    fn check(&mut self, type_checker: &mut TypeChecker) -> Result<Type, TypeCheckError> {
+        let place = self.check_base(type_checker)?;
+        let field = self.check_field(type_checker)?;
+        if !type_checker.resolve(&field).is_copy() {
+            return Err(TypeCheckError::MoveOutOfFieldNoToken {
+                r#type: Box::new(self.tuple.clone()),
+                index: self.index,
+            });
+        }
+        if let Some(Place {
+            root: Some(root),
+            location,
+            ..
+        }) = &place
+        {
+            type_checker.access(*root, Access::Read, location)?;
+        }
+        Ok(field)
+    }
+
+    fn gen_ir(&self, ir_generator: &mut IrGenerator) {
+        if self.place || self.deref {
+            let address = self.gen_address(ir_generator);
+            let t = ir_generator.resolve(&self.r#type).to_ir();
+            let result = ir_generator.new_value();
+            ir_generator.emit(&format!("%{} = load {}, {}* {}", result, t, t, address));
+            ir_generator.value = format!("%{}", result);
+            return;
+        }
+        self.value.gen_ir(ir_generator);
+        let value = ir_generator.pop_value();
+        let result = ir_generator.new_value();
+        ir_generator.emit(&format!(
+            "%{} = extractvalue {} {}, {}",
+            result,
+            ir_generator.resolve(&self.tuple).to_ir(),
+            value,
+            self.index
+        ));
+        if self.drop {
+            ir_generator.drop_value(&value, &self.tuple);
+        }
+        ir_generator.value = format!("%{}", result);
+    }
+
+    // Only a field of a value can be read, there are no references at compile time
+    fn evaluate(&self, evaluator: &mut Evaluator) -> Result<Value, Interrupt> {
+        if self.deref {
+            return Err(EvaluationError::NotConstNoToken.into());
+        }
+        let Value::Tuple(mut elements) = self.value.evaluate(evaluator)? else {
+            unreachable!()
+        };
+        Ok(elements.swap_remove(self.index))
+    }
+}
+
+impl Field {
+    // Checks the value the field is in and returns the place it is in, if it is in one. A
+    // reference is followed to what it points to
+    fn check_base(
+        &mut self,
+        type_checker: &mut TypeChecker,
+    ) -> Result<Option<Place>, TypeCheckError> {
+        self.place = place::is_place(&self.value, type_checker);
+        let (t, place) = if self.place {
+            let place = place::check_place(&mut self.value, type_checker)?;
+            (place.r#type.clone(), Some(place))
+        } else {
+            (self.value.check(type_checker)?, None)
+        };
+        self.tuple = type_checker.resolve(&t);
+        if let Type::Reference { mutable, referent } = self.tuple.clone() {
+            self.deref = true;
+            self.tuple = *referent;
+            let (root, location) = match place {
+                Some(place) => (place.root, place.location),
+                None => (None, self.value.location.clone()),
+            };
+            return Ok(Some(Place {
+                r#type: self.tuple.clone(),
+                root,
+                through_deref: true,
+                mutable,
+                location,
+            }));
+        }
+        if place.is_none() {
+            self.drop = type_checker.needs_drop(&t);
+        }
+        Ok(place)
+    }
+
+    fn check_field(&mut self, type_checker: &TypeChecker) -> Result<Type, TypeCheckError> {
+        let fields = match &self.tuple {
+            Type::Tuple(elements) => elements.clone(),
+            Type::Struct(symbol) => type_checker.structs[&symbol.to_string()].fields.clone(),
+            _ => Vec::new(),
+        };
+        let Some(field) = fields.get(self.index) else {
+            return Err(TypeCheckError::NoFieldNoToken {
+                r#type: Box::new(self.tuple.clone()),
+                index: self.index,
+            });
+        };
+        self.r#type = field.clone();
+        Ok(field.clone())
+    }
+
+    // A field of a temporary is not a place, unless the temporary is a reference
+    pub fn check_place(&mut self, type_checker: &mut TypeChecker) -> Result<Place, TypeCheckError> {
+        let Some(place) = self.check_base(type_checker)? else {
+            return Err(TypeCheckError::NotAPlace(self.value.location.clone()));
+        };
+        Ok(Place {
+            r#type: self.check_field(type_checker)?,
+            ..place
+        })
+    }
+
+    pub fn gen_address(&self, ir_generator: &mut IrGenerator) -> String {
+        let base = if self.deref {
+            self.value.gen_ir(ir_generator);
+            ir_generator.pop_value()
+        } else {
+            place::gen_address(&self.value, ir_generator)
+        };
+        let t = ir_generator.resolve(&self.tuple).to_ir();
+        let result = ir_generator.new_value();
+        ir_generator.emit(&format!(
+            "%{} = getelementptr {}, {}* {}, i32 0, i32 {}",
+            result, t, t, base, self.index
+        ));
+        format!("%{}", result)
+    }
+}
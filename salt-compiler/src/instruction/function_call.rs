@@ -1,34 +1,224 @@
 use crate::{
     error::{ParseError, TypeCheckError},
+    evaluator::{Evaluator, Interrupt, Value},
     ir_generator::IrGenerator,
-    lexer::{TokenKind, Type},
-    parser::Parser,
-    type_checker::TypeChecker,
+    lexer::{Location, Token, TokenKind, Type},
+    parser::{Parser, Statement, StatementKind},
+    type_checker::{FunctionSignature, TypeChecker},
 };
 
+use super::Instruction;
+
 #[derive(Debug, Clone)]
 pub struct FunctionCall {
     name: String,
+    // The key of the function the name resolves to where it is called
+    key: String,
+    arguments: Vec<Statement>,
+    argument_types: Vec<Type>,
+    // The struct the call builds when the name is a struct and not a function
+    constructor: Option<Type>,
 }
 
-impl super::Instruction for FunctionCall {
+impl Instruction for FunctionCall {
     fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
         let name = parser.expect_ident()?;
         parser.expect(&TokenKind::OpenParen)?;
+        let mut arguments = Vec::new();
+        while let Some(token) = parser.peek()
+            && token.kind != TokenKind::CloseParen
+        {
+            arguments.push(Statement::parse_expression(parser)?);
+            if let Some(Token {
+                kind: TokenKind::Comma,
+                ..
+            }) = parser.peek()
+            {
+                parser.bump();
+            } else {
+                break;
+            }
+        }
         parser.expect(&TokenKind::CloseParen)?;
-        Ok(Self { name })
+        Ok(Self {
+            key: name.clone(),
+            name,
+            arguments,
+            argument_types: Vec::new(),
+            constructor: None,
+        })
     }
 
-    fn check(&self, type_checker: &mut TypeChecker) -> Result<Type, TypeCheckError> {
-        if let Some(function) = type_checker.functions.get(&self.name) {
-            Ok(function.return_type)
+    fn check(&mut self, type_checker: &mut TypeChecker) -> Result<Type, TypeCheckError> {
+        // A struct is built by calling it with its fields
+        let function = if let Some(key) = type_checker.function_key(&self.name) {
+            self.key = key.clone();
+            type_checker.functions[key].clone()
+        } else if let Some(signature) = type_checker.struct_signature(&self.name) {
+            let t = Type::Struct(signature.symbol.clone());
+            self.constructor = Some(t.clone());
+            FunctionSignature {
+                symbol: String::new(),
+                parameters: signature.fields.clone(),
+                variadic: false,
+                return_type: t,
+                c_abi: false,
+                constant: true,
+                raw: false,
+                location: Location::default(),
+            }
         } else {
-            Err(TypeCheckError::UndeclaredFunctionNoToken)
+            return Err(TypeCheckError::UndeclaredFunctionNoToken);
+        };
+        if type_checker.in_const() && !function.constant {
+            return Err(TypeCheckError::NotConstCallNoToken);
+        }
+        if function.raw && !type_checker.allows_unsafe() {
+            return Err(TypeCheckError::UnsafeCallNoToken {
+                declaration: Box::new(function.location),
+            });
+        }
+
+        if self.arguments.len() < function.parameters.len()
+            || (!function.variadic && self.arguments.len() > function.parameters.len())
+        {
+            return Err(TypeCheckError::ArgumentCountNoToken {
+                expected: function.parameters.len(),
+                actual: self.arguments.len(),
+                variadic: function.variadic,
+            });
+        }
+
+        self.argument_types.clear();
+        for (i, argument) in self.arguments.iter_mut().enumerate() {
+            // A `&mut` binding is lent to the function rather than given to it
+            let reborrowed = match &mut argument.kind {
+                StatementKind::Variable(variable) => variable.check_reborrow(type_checker)?,
+                _ => None,
+            };
+            let t = match reborrowed {
+                Some(t) => t,
+                None => argument.check(type_checker)?,
+            };
+            match function.parameters.get(i) {
+                Some(expected) => type_checker.unify(expected, &t, &argument.location, None)?,
+                None if t == Type::UNIT => {
+                    return Err(TypeCheckError::MismatchedType {
+                        expected: Box::new((Type::Any, None)),
+                        actual: Box::new((t, None)),
+                        location: argument.location.clone(),
+                    });
+                }
+                None => (),
+            }
+            self.argument_types.push(t);
         }
+
+        Ok(function.return_type.clone())
     }
 
     fn gen_ir(&self, ir_generator: &mut IrGenerator) {
-        let return_type = ir_generator.env.get(&self.name).unwrap().return_type;
-        ir_generator.stash = format!("call {} @{}()", return_type.to_ir(), self.name)
+        if let Some(t) = &self.constructor {
+            return self.gen_constructor_ir(t, ir_generator);
+        }
+        let function = ir_generator.env[&self.key].clone();
+        let mut arguments = Vec::new();
+        for (i, (argument, t)) in self.arguments.iter().zip(&self.argument_types).enumerate() {
+            argument.gen_ir(ir_generator);
+            let value = ir_generator.pop_value();
+            let t = &ir_generator.resolve(t);
+
+            // C promotes variadic arguments smaller than an int
+            let promotion = match t {
+                Type::Bool => Some("zext"),
+                Type::Int(int) if int.bits() < 32 && int.signed() => Some("sext"),
+                Type::Int(int) if int.bits() < 32 => Some("zext"),
+                _ => None,
+            };
+            if matches!(t, Type::Str | Type::ByteStr) {
+                arguments.push(ir_generator.slice_argument(&value, t, function.c_abi));
+            } else if i >= function.parameters.len()
+                && let Some(promotion) = promotion
+            {
+                let promoted = ir_generator.new_value();
+                ir_generator.emit(&format!(
+                    "%{} = {} {} {} to i32",
+                    promoted,
+                    promotion,
+                    t.to_ir(),
+                    value
+                ));
+                arguments.push(format!("i32 %{}", promoted));
+            } else {
+                arguments.push(format!("{} {}", t.to_ir(), value));
+            }
+        }
+
+        // Calls to variadic functions have to spell out the function type
+        let callee = if function.variadic {
+            format!(
+                "{} ({})",
+                function.return_type.return_ir(),
+                function.parameters_ir()
+            )
+        } else {
+            function.return_type.return_ir()
+        };
+        let call = format!(
+            "call {} @{}({})",
+            callee,
+            function.symbol,
+            arguments.join(", ")
+        );
+
+        if function.return_type == Type::Never {
+            ir_generator.emit(&call);
+            ir_generator.unreachable("unreachable");
+        } else if function.return_type == Type::UNIT {
+            ir_generator.emit(&call);
+            ir_generator.value = "zeroinitializer".to_string();
+        } else {
+            let value = ir_generator.new_value();
+            ir_generator.emit(&format!("%{} = {}", value, call));
+            ir_generator.value = format!("%{}", value);
+        }
+    }
+
+    // Building a struct is always possible, a call runs the body of the `const fn`
+    fn evaluate(&self, evaluator: &mut Evaluator) -> Result<Value, Interrupt> {
+        let mut arguments = Vec::new();
+        for argument in &self.arguments {
+            arguments.push(argument.evaluate(evaluator)?);
+        }
+        if self.constructor.is_some() {
+            return Ok(Value::Tuple(arguments));
+        }
+        evaluator.call(&self.key, arguments)
+    }
+}
+
+impl FunctionCall {
+    fn gen_constructor_ir(&self, t: &Type, ir_generator: &mut IrGenerator) {
+        let mut result = "undef".to_string();
+        for (i, (argument, field)) in self.arguments.iter().zip(&self.argument_types).enumerate() {
+            argument.gen_ir(ir_generator);
+            let value = ir_generator.pop_value();
+            let field = ir_generator.resolve(field);
+            let next = ir_generator.new_value();
+            ir_generator.emit(&format!(
+                "%{} = insertvalue {} {}, {} {}, {}",
+                next,
+                t.to_ir(),
+                result,
+                field.to_ir(),
+                value,
+                i
+            ));
+            result = format!("%{}", next);
+        }
+        ir_generator.value = match self.arguments.is_empty() {
+            true => "zeroinitializer".to_string(),
+            false => result,
+        };
     }
 }
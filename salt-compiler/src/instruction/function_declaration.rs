@@ -1,97 +1,289 @@
 use crate::{
-    error::{ParseError, TypeCheckError},
+    error::{EvaluationError, ParseError, TypeCheckError},
+    evaluator::{Evaluator, Interrupt, Value},
     ir_generator::IrGenerator,
-    lexer::{Keyword, Token, TokenKind, Type},
+    lexer::{Keyword, Location, Token, TokenKind, Type},
     parser::{Parser, Statement, StatementKind},
-    type_checker::TypeChecker,
+    type_checker::{Cleanup, FunctionSignature, TypeChecker},
+};
+
+use super::{
+    Instruction,
+    attribute::{self, Attribute, Target},
 };
 
+#[derive(Debug, Clone)]
+pub struct Parameter {
+    pub name: String,
+    pub r#type: Type,
+    // The written type
+    pub location: Location,
+}
+
 #[derive(Debug, Clone)]
 pub struct FunctionDeclaration {
     pub name: String,
+    pub location: Location,
+    pub doc: Vec<String>,
+    pub parameters: Vec<Parameter>,
     pub body: Box<Statement>,
+    // The end of the body, where the parameters go out of scope
+    end: Location,
     pub return_type: Type,
+    // The written return type, or the name when it defaults to `()`
+    pub return_type_location: Location,
+    // The type the function is implemented for when it is in an `impl`
+    pub receiver: Option<String>,
+    pub attributes: Vec<Attribute>,
+    // The key the function is known by in the symbol table, see `TypeChecker::function_key`
+    key: String,
 
+    pub public: bool,
+    // A `const fn` can also be run by the evaluator to compute constants
+    pub constant: bool,
     pub raw: bool,
+    // The body never finishes, so the function ends without a `ret`
+    diverges: bool,
+    // The parameters that still own a value at the end of the body
+    cleanup: Vec<Cleanup>,
 }
 
-impl super::Instruction for FunctionDeclaration {
+impl Instruction for FunctionDeclaration {
     fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
-        let raw = match parser.bump() {
+        let doc = parser.doc_comments();
+        let attributes = parser.expect_attributes()?;
+        let public = parser.expect_visibility();
+        let constant = match parser.peek() {
+            Some(Token {
+                kind: TokenKind::Keyword(Keyword::Const),
+                ..
+            }) => {
+                parser.bump();
+                true
+            }
+            _ => false,
+        };
+        let raw = match parser.peek() {
             Some(Token {
                 kind: TokenKind::Keyword(Keyword::Raw),
                 ..
             }) => {
-                parser.expect(&TokenKind::Keyword(Keyword::Fn))?;
+                parser.bump();
                 true
             }
             _ => false,
         };
+        parser.expect(&TokenKind::Keyword(Keyword::Fn))?;
         let name = parser.expect_ident()?;
-        parser.expect(&TokenKind::OpenParen)?;
-        parser.expect(&TokenKind::CloseParen)?;
+        let location = parser.previous().location.clone();
+        let mut return_type_location = location.clone();
+        let (parameters, _) = parser.expect_parameters(false)?;
+        if let Some(Token {
+            kind: TokenKind::Arrow,
+            ..
+        }) = parser.peek()
+            && let Some(token) = parser.peek_nth(1)
+        {
+            return_type_location = token.location;
+        }
         let return_type = parser.expect_optional_type()?;
+        return_type_location = return_type_location.to(&parser.previous().location);
 
-        let body = Box::new(Statement::parse(parser)?);
+        let mut body = Box::new(Statement::parse(parser)?);
+        let end = parser.previous().location.clone();
+        if let StatementKind::Block(block) = &mut body.kind {
+            block.function_body = true;
+        }
 
         Ok(Self {
+            key: name.clone(),
             name,
+            location,
+            doc,
+            parameters,
             body,
+            end,
             return_type,
+            return_type_location,
+            receiver: None,
+            attributes,
 
+            public,
+            constant,
             raw,
+            diverges: false,
+            cleanup: Vec::new(),
         })
     }
 
-    fn check(&self, type_checker: &mut TypeChecker) -> Result<Type, TypeCheckError> {
+    fn check(&mut self, type_checker: &mut TypeChecker) -> Result<Type, TypeCheckError> {
+        self.key = match &self.receiver {
+            Some(receiver) => {
+                format!(
+                    "{}::{}",
+                    type_checker.struct_key(receiver).unwrap(),
+                    self.name
+                )
+            }
+            None => type_checker.function_key(&self.name).unwrap().clone(),
+        };
+        // The kernel has nothing to return to
+        if self.name == "main" && type_checker.at_top_level() && self.return_type != Type::Never {
+            return Err(TypeCheckError::MainReturnType(
+                self.return_type_location.clone(),
+            ));
+        }
+
+        attribute::check_attributes(&mut self.attributes, Target::Function, type_checker)?;
+        if attribute::find(&self.attributes, "panic_handler").is_some() {
+            type_checker.new_panic_handler(self)?;
+        }
+        for parameter in &mut self.parameters {
+            type_checker.check_type(&mut parameter.r#type, &parameter.location)?;
+        }
+        type_checker.check_type(&mut self.return_type, &self.return_type_location)?;
+
         type_checker.new_function(self);
         let mut t = self.body.check(type_checker)?;
-        t = match self.body.returns {
+        t = match self.body.returns || t == Type::Never {
             true => t,
-            false => Type::Void,
+            false => Type::UNIT,
         };
-        type_checker.finish_function();
-        if self.return_type == t {
-            Ok(t)
-        } else {
-            Err(TypeCheckError::MismatchedType {
-                location: self.body.last().location.clone(),
-                expected: self.return_type,
-                actual: t,
-            })
-        }
+        self.diverges = t == Type::Never;
+        // An empty body has no statement to point at, the return type is what it fails to give
+        let (location, origin) = match self.body.last().kind {
+            StatementKind::Empty => (&self.return_type_location, None),
+            _ => (&self.body.last().location, Some(&self.return_type_location)),
+        };
+        type_checker.unify(&self.return_type, &t, location, origin)?;
+        self.cleanup = type_checker.finish_scope(&self.end, &self.body.location)?;
+        type_checker.finish_function()?;
+        Ok(Type::UNIT)
     }
 
     fn gen_ir(&self, ir_generator: &mut IrGenerator) {
+        // Only public items at the top of the module, the entry point and items that keep their
+        // name are visible to the linker
+        let linkage = if ir_generator.values.is_empty()
+            && (self.public || self.name == "main" || self.no_mangle())
+        {
+            ""
+        } else {
+            "internal "
+        };
         ir_generator.new_function();
         let mut ir = String::new();
+        for line in &self.doc {
+            ir.push_str(&format!(";{line}\n"));
+        }
         ir.push_str(&format!(
-            "define {} @{}() {{\nentry:\n",
-            self.return_type.to_ir(),
-            self.name
+            "define {}{} @{}({}){}{} {{\nentry:\n",
+            linkage,
+            self.return_type.return_ir(),
+            ir_generator.env[&self.key()].symbol,
+            self.parameters
+                .iter()
+                .map(|parameter| format!("{} %arg.{}", parameter.r#type.to_ir(), parameter.name))
+                .collect::<Vec<_>>()
+                .join(", "),
+            self.function_attributes_ir(),
+            match attribute::find(&self.attributes, "section").and_then(Attribute::string) {
+                Some(section) => format!(" section \"{}\"", section),
+                None => String::new(),
+            }
         ));
 
+        // The parameters get stack slots like any other binding
+        let mut body = String::new();
+        for (i, parameter) in self.parameters.iter().enumerate() {
+            let t = parameter.r#type.to_ir();
+            let address = ir_generator.new_variable(i, &parameter.r#type);
+            body.push_str(&format!(
+                "  store {} %arg.{}, {}* {}\n",
+                t, parameter.name, t, address
+            ));
+        }
+
+        let outer = ir_generator.pop_stash();
         self.body.gen_ir(ir_generator);
-        match self.body.kind {
-            StatementKind::FunctionDeclaration(_) => (),
-            _ => {
-                ir.push_str(&ir_generator.pop_stash());
-            }
+        let value = ir_generator.pop_value();
+        body.push_str(&ir_generator.pop_stash());
+        ir_generator.clean_up(&self.cleanup);
+        for line in ir_generator.pop_stash().lines() {
+            body.push_str(&format!("  {line}\n"));
         }
+        ir_generator.stash = outer;
 
-        if self.body.last().returns {
-            ir.push_str(&format!(
-                "  ret {} %{}\n",
-                self.return_type.to_ir(),
-                ir_generator.values.last().unwrap() - 1
-            ))
-        } else if self.return_type == Type::Void {
+        ir.push_str(&ir_generator.finish_function());
+        ir.push_str(&body);
+        if self.diverges {
+            ir.push_str("  unreachable\n");
+        } else if self.return_type == Type::UNIT {
             ir.push_str("  ret void\n");
+        } else if self.body.returns {
+            ir.push_str(&format!("  ret {} {}\n", self.return_type.to_ir(), value))
         }
         ir.push('}');
         ir.push('\n');
 
-        ir_generator.finish_function();
         ir_generator.function_declarations.push(ir);
     }
 }
+
+impl FunctionDeclaration {
+    pub fn key(&self) -> String {
+        self.key.clone()
+    }
+
+    // Runs the body in the frame the evaluator set up with the arguments, the parameters can not
+    // be dropped at compile time
+    pub fn evaluate_call(&self, evaluator: &mut Evaluator) -> Result<Value, Interrupt> {
+        if !self.cleanup.is_empty() {
+            return Err(EvaluationError::NotConst(self.location.clone()).into());
+        }
+        match self.body.evaluate(evaluator) {
+            Ok(value) | Err(Interrupt::Return(value)) => Ok(value),
+            Err(interrupt) => Err(interrupt),
+        }
+    }
+
+    // `#[no_mangle]` functions are emitted under their own name
+    pub fn no_mangle(&self) -> bool {
+        attribute::find(&self.attributes, "no_mangle").is_some()
+    }
+
+    // The LLVM function attributes that follow the parameters
+    fn function_attributes_ir(&self) -> String {
+        let mut attributes = Vec::new();
+        if self.return_type == Type::Never {
+            attributes.push("noreturn");
+        }
+        if let Some(inline) = attribute::find(&self.attributes, "inline") {
+            attributes.push(inline.inline().unwrap());
+        }
+        if attribute::find(&self.attributes, "cold").is_some() {
+            attributes.push("cold");
+        }
+        attributes
+            .iter()
+            .map(|attribute| format!(" {attribute}"))
+            .collect()
+    }
+
+    pub fn signature(&self, symbol: String) -> FunctionSignature {
+        FunctionSignature {
+            symbol,
+            parameters: self
+                .parameters
+                .iter()
+                .map(|parameter| parameter.r#type.clone())
+                .collect(),
+            variadic: false,
+            return_type: self.return_type.clone(),
+            c_abi: false,
+            constant: self.constant,
+            raw: self.raw,
+            location: self.location.clone(),
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use crate::{
+    error::{ParseError, TypeCheckError},
+    ir_generator::IrGenerator,
+    lexer::{Keyword, Location, TokenKind, Type},
+    parser::Parser,
+    type_checker::TypeChecker,
+};
+
+use super::{Instruction, function_declaration::FunctionDeclaration};
+
+#[derive(Debug, Clone)]
+pub struct Impl {
+    pub r#trait: String,
+    trait_location: Location,
+    pub r#type: String,
+    type_location: Location,
+    pub functions: Vec<FunctionDeclaration>,
+}
+
+impl Instruction for Impl {
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        parser.bump();
+        let trait_location = parser.peek().ok_or(ParseError::UnexpectedEOF)?.location;
+        let r#trait = parser.expect_ident()?;
+        parser.expect(&TokenKind::Keyword(Keyword::For))?;
+        let type_location = parser.peek().ok_or(ParseError::UnexpectedEOF)?.location;
+        let r#type = parser.expect_ident()?;
+
+        parser.expect(&TokenKind::OpenBrace)?;
+        let mut functions = Vec::new();
+        while let Some(token) = parser.peek()
+            && token.kind != TokenKind::CloseBrace
+        {
+            let mut function = FunctionDeclaration::parse(parser)?;
+            function.receiver = Some(r#type.clone());
+            functions.push(function);
+        }
+        parser.expect(&TokenKind::CloseBrace)?;
+
+        Ok(Self {
+            r#trait,
+            trait_location,
+            r#type,
+            type_location,
+            functions,
+        })
+    }
+
+    // `Drop` is the only trait, it has the one function that takes the value being dropped
+    fn check(&mut self, type_checker: &mut TypeChecker) -> Result<Type, TypeCheckError> {
+        if self.r#trait != "Drop" {
+            return Err(TypeCheckError::UnknownTrait(self.trait_location.clone()));
+        }
+        let Some(signature) = type_checker.struct_signature(&self.r#type) else {
+            return Err(TypeCheckError::UndeclaredType {
+                name: self.r#type.as_str().into(),
+                location: self.type_location.clone(),
+            });
+        };
+        let t = Type::Struct(signature.symbol.clone());
+        match self.functions.as_slice() {
+            [] => return Err(TypeCheckError::InvalidDrop(self.type_location.clone())),
+            [_, extra, ..] => return Err(TypeCheckError::InvalidDrop(extra.location.clone())),
+            [function] => {
+                if function.name != "drop"
+                    || function.parameters.len() != 1
+                    || type_checker.resolve_type(&function.parameters[0].r#type) != t
+                    || function.return_type != Type::UNIT
+                {
+                    return Err(TypeCheckError::InvalidDrop(function.location.clone()));
+                }
+            }
+        }
+
+        for function in &mut self.functions {
+            function.check(type_checker)?;
+        }
+        Ok(Type::UNIT)
+    }
+
+    fn gen_ir(&self, ir_generator: &mut IrGenerator) {
+        for function in &self.functions {
+            function.gen_ir(ir_generator);
+        }
+    }
+}
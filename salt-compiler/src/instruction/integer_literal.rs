@@ -0,0 +1,66 @@
+use crate::{
+    error::{ParseError, TypeCheckError},
+    evaluator::{Evaluator, Interrupt, Value},
+    ir_generator::IrGenerator,
+    lexer::{IntType, Location, TokenKind, Type},
+    parser::Parser,
+    type_checker::TypeChecker,
+};
+
+#[derive(Debug, Clone)]
+pub struct IntegerLiteral {
+    value: u128,
+    // Whether a minus sign comes before it
+    negative: bool,
+    suffix: Option<IntType>,
+    location: Location,
+    r#type: Type,
+}
+
+impl super::Instruction for IntegerLiteral {
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        let negative = parser.peek().map(|token| token.kind) == Some(TokenKind::Minus);
+        let start = if negative { parser.bump() } else { None };
+        let token = parser.bump().unwrap();
+        if let TokenKind::IntegerLiteral((value, suffix)) = token.kind {
+            Ok(Self {
+                value,
+                negative,
+                suffix,
+                location: match start {
+                    Some(start) => start.location.to(&token.location),
+                    None => token.location,
+                },
+                r#type: Type::UNIT,
+            })
+        } else {
+            unreachable!()
+        }
+    }
+
+    fn check(&mut self, type_checker: &mut TypeChecker) -> Result<Type, TypeCheckError> {
+        self.r#type =
+            type_checker.new_literal(self.value, self.negative, self.suffix, &self.location);
+        Ok(self.r#type.clone())
+    }
+
+    fn gen_ir(&self, ir_generator: &mut IrGenerator) {
+        ir_generator.value = match self.negative {
+            true => format!("-{}", self.value),
+            false => self.value.to_string(),
+        };
+    }
+
+    // Integers are kept as their bits, so a negative one is in two's complement
+    fn evaluate(&self, evaluator: &mut Evaluator) -> Result<Value, Interrupt> {
+        if !self.negative {
+            return Ok(Value::Int(self.value));
+        }
+        let Type::Int(int) = evaluator.resolve(&self.r#type) else {
+            unreachable!()
+        };
+        Ok(Value::Int(
+            self.value.wrapping_neg() & (u128::MAX >> (128 - int.bits())),
+        ))
+    }
+}
@@ -1,51 +1,223 @@
 use crate::{
-    error::{ParseError, TypeCheckError},
+    error::{EvaluationError, ParseError, TypeCheckError},
+    evaluator::{self, Evaluator, Interrupt, Value},
     ir_generator::IrGenerator,
     lexer::{Token, TokenKind, Type},
-    parser::Parser,
+    parser::{Parser, Statement},
     type_checker::TypeChecker,
 };
 
+use super::{
+    Instruction,
+    binary::{self, Operator},
+};
+
 #[derive(Debug, Clone)]
 pub enum Intrinsic {
+    // Waits for the next interrupt
     Hlt,
+    // Stops the CPU for good
+    Halt,
+    // Adds two integers with the given behaviour on overflow, whatever kind of build it is
+    Add {
+        overflow: Overflow,
+        left: Box<Statement>,
+        right: Box<Statement>,
+        r#type: Type,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Overflow {
+    // `@wrapping_add`, the result wraps around
+    Wrapping,
+    // `@checked_add`, the wrapped result and whether it overflowed
+    Checked,
+    // `@saturating_add`, the result stops at the smallest or largest value
+    Saturating,
 }
 
-impl super::Instruction for Intrinsic {
+impl Overflow {
+    fn name(self) -> &'static str {
+        match self {
+            Overflow::Wrapping => "@wrapping_add",
+            Overflow::Checked => "@checked_add",
+            Overflow::Saturating => "@saturating_add",
+        }
+    }
+}
+
+impl Instruction for Intrinsic {
     fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
-        let symbol = parser.bump();
-        let intrinsic = if let Some(Token {
+        let Some(Token {
             kind: TokenKind::Intrinsic(symbol),
+            location,
             ..
-        }) = symbol
-        {
-            match symbol.as_str() {
-                "@hlt" => Self::Hlt,
-                _ => unreachable!(),
-            }
-        } else {
+        }) = parser.bump()
+        else {
             unreachable!();
         };
+        let overflow = match symbol.as_str() {
+            "@hlt" | "@halt" => None,
+            "@wrapping_add" => Some(Overflow::Wrapping),
+            "@checked_add" => Some(Overflow::Checked),
+            "@saturating_add" => Some(Overflow::Saturating),
+            _ => return Err(ParseError::UnknownIntrinsic(location)),
+        };
         parser.expect(&TokenKind::OpenParen)?;
+        let intrinsic = match overflow {
+            None if symbol == "@hlt" => Self::Hlt,
+            None => Self::Halt,
+            Some(overflow) => {
+                let left = Box::new(Statement::parse_expression(parser)?);
+                parser.expect(&TokenKind::Comma)?;
+                let right = Box::new(Statement::parse_expression(parser)?);
+                Self::Add {
+                    overflow,
+                    left,
+                    right,
+                    r#type: Type::UNIT,
+                }
+            }
+        };
         parser.expect(&TokenKind::CloseParen)?;
         Ok(intrinsic)
     }
 
-    fn check(&self, type_checker: &mut TypeChecker) -> Result<Type, TypeCheckError> {
-        match self {
-            Intrinsic::Hlt => {
-                if !type_checker.in_raw_function() {
-                    Err(TypeCheckError::UnsafeUseNoToken)
-                } else {
-                    Ok(Type::Void)
-                }
+    fn check(&mut self, type_checker: &mut TypeChecker) -> Result<Type, TypeCheckError> {
+        let (overflow, left, right, r#type) = match self {
+            Intrinsic::Hlt | Intrinsic::Halt if !type_checker.allows_unsafe() => {
+                return Err(TypeCheckError::UnsafeUseNoToken);
+            }
+            Intrinsic::Hlt => return Ok(Type::UNIT),
+            Intrinsic::Halt => return Ok(Type::Never),
+            Intrinsic::Add {
+                overflow,
+                left,
+                right,
+                r#type,
+            } => (*overflow, left, right, r#type),
+        };
+
+        // The operands are checked like those of `+`
+        let left_type = left.check(type_checker)?;
+        let right_type = right.check(type_checker)?;
+        for (t, operand) in [(&left_type, &left), (&right_type, &right)] {
+            if !matches!(
+                type_checker.resolve(t),
+                Type::Int(_) | Type::IntVar(_) | Type::Never
+            ) {
+                return Err(TypeCheckError::InvalidOperand {
+                    location: operand.location.clone(),
+                    r#type: Box::new(t.clone()),
+                    operator: overflow.name(),
+                });
             }
         }
+        type_checker.unify(
+            &left_type,
+            &right_type,
+            &right.location,
+            Some(&left.location),
+        )?;
+        *r#type = if left_type == Type::Never {
+            right_type
+        } else {
+            left_type
+        };
+        Ok(match overflow {
+            Overflow::Checked => Type::Tuple(vec![r#type.clone(), Type::Bool]),
+            Overflow::Wrapping | Overflow::Saturating => r#type.clone(),
+        })
     }
 
     fn gen_ir(&self, ir_generator: &mut IrGenerator) {
-        match self {
-            Intrinsic::Hlt => ir_generator.stash = String::from("call void asm \"hlt\", \"\"()"),
-        }
+        let (overflow, left, right, r#type) = match self {
+            Intrinsic::Hlt => {
+                ir_generator.emit("call void asm sideeffect \"hlt\", \"\"()");
+                ir_generator.value = "zeroinitializer".to_string();
+                return;
+            }
+            // A non maskable interrupt can still wake the CPU, so it halts again
+            Intrinsic::Halt => {
+                let label = ir_generator.new_label();
+                ir_generator.emit(&format!("br label %halt{}", label));
+                ir_generator.emit(&format!("halt{}:", label));
+                ir_generator.emit("call void asm sideeffect \"cli; hlt\", \"\"()");
+                ir_generator.unreachable(&format!("br label %halt{}", label));
+                return;
+            }
+            Intrinsic::Add {
+                overflow,
+                left,
+                right,
+                r#type,
+            } => (*overflow, left, right, r#type),
+        };
+
+        left.gen_ir(ir_generator);
+        let left = ir_generator.pop_value();
+        right.gen_ir(ir_generator);
+        let right = ir_generator.pop_value();
+        let Type::Int(int) = ir_generator.resolve(r#type) else {
+            ir_generator.value = "undef".to_string();
+            return;
+        };
+        let t = Type::Int(int).to_ir();
+        let sign = if int.signed() { "s" } else { "u" };
+        ir_generator.value = match overflow {
+            Overflow::Wrapping => {
+                let result = ir_generator.new_value();
+                ir_generator.emit(&format!("%{} = add {} {}, {}", result, t, left, right));
+                format!("%{}", result)
+            }
+            // LLVM returns the same `{ iN, i1 }` the tuple is
+            Overflow::Checked => {
+                binary::with_overflow(ir_generator, &format!("{sign}add"), int, &left, &right)
+            }
+            Overflow::Saturating => {
+                let function = format!("llvm.{}add.sat.{}", sign, t);
+                ir_generator
+                    .declare_symbol(&function, &format!("declare {t} @{function}({t}, {t})\n"));
+                let result = ir_generator.new_value();
+                ir_generator.emit(&format!(
+                    "%{} = call {t} @{}({t} {}, {t} {})",
+                    result, function, left, right
+                ));
+                format!("%{}", result)
+            }
+        };
+    }
+
+    fn evaluate(&self, evaluator: &mut Evaluator) -> Result<Value, Interrupt> {
+        let Intrinsic::Add {
+            overflow,
+            left,
+            right,
+            r#type,
+        } = self
+        else {
+            return Err(EvaluationError::NotConstNoToken.into());
+        };
+        let left = left.evaluate(evaluator)?;
+        let right = right.evaluate(evaluator)?;
+        let (Type::Int(int), Value::Int(left), Value::Int(right)) =
+            (evaluator.resolve(r#type), left, right)
+        else {
+            unreachable!()
+        };
+        let (bits, overflowed) = Operator::Add.apply(int, left, right).unwrap();
+        Ok(match overflow {
+            Overflow::Wrapping => Value::Int(bits),
+            Overflow::Checked => {
+                Value::Tuple(vec![Value::Int(bits), Value::Int(overflowed as u128)])
+            }
+            // Adding a negative number can only go past the smallest value
+            Overflow::Saturating if overflowed && evaluator::to_i128(right, int) < 0 => {
+                Value::Int(evaluator::from_i128(int.min(), int).0)
+            }
+            Overflow::Saturating if overflowed => Value::Int(int.max()),
+            Overflow::Saturating => Value::Int(bits),
+        })
     }
 }
@@ -0,0 +1,243 @@
+use crate::{
+    error::{ParseError, TypeCheckError},
+    evaluator::{Evaluator, Interrupt, Value},
+    ir_generator::IrGenerator,
+    lexer::{Keyword, Location, Token, TokenKind, Type},
+    parser::{Parser, Statement},
+    type_checker::TypeChecker,
+};
+
+use super::Instruction;
+
+#[derive(Debug, Clone)]
+pub enum Pattern {
+    // The name, the index the type checker gave the binding and whether it is `mut`
+    Binding(String, usize, bool),
+    // `_` drops the value right away
+    Wildcard,
+    Tuple(Vec<Pattern>, Location),
+}
+
+impl Pattern {
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        match parser.peek() {
+            Some(Token {
+                kind: TokenKind::OpenParen,
+                location,
+                ..
+            }) => {
+                parser.bump();
+                let mut patterns = Vec::new();
+                let mut trailing_comma = false;
+                while let Some(token) = parser.peek()
+                    && token.kind != TokenKind::CloseParen
+                {
+                    patterns.push(Pattern::parse(parser)?);
+                    trailing_comma = false;
+                    if let Some(Token {
+                        kind: TokenKind::Comma,
+                        ..
+                    }) = parser.peek()
+                    {
+                        parser.bump();
+                        trailing_comma = true;
+                    } else {
+                        break;
+                    }
+                }
+                parser.expect(&TokenKind::CloseParen)?;
+                if patterns.len() == 1 && !trailing_comma {
+                    return Ok(patterns.pop().unwrap());
+                }
+                Ok(Pattern::Tuple(
+                    patterns,
+                    location.to(&parser.previous().location),
+                ))
+            }
+            Some(Token {
+                kind: TokenKind::Keyword(Keyword::Mut),
+                ..
+            }) => {
+                parser.bump();
+                Ok(Pattern::Binding(parser.expect_ident()?, 0, true))
+            }
+            _ => match parser.expect_ident()?.as_str() {
+                "_" => Ok(Pattern::Wildcard),
+                name => Ok(Pattern::Binding(name.to_string(), 0, false)),
+            },
+        }
+    }
+
+    fn check(&mut self, t: &Type, type_checker: &mut TypeChecker) -> Result<(), TypeCheckError> {
+        let resolved = type_checker.resolve(t);
+        match self {
+            Pattern::Binding(name, local, mutable) => {
+                *local = type_checker.new_variable(name, t.clone(), *mutable)
+            }
+            Pattern::Wildcard => (),
+            Pattern::Tuple(patterns, location) => match resolved {
+                Type::Tuple(elements) if elements.len() == patterns.len() => {
+                    for (pattern, element) in patterns.iter_mut().zip(&elements) {
+                        pattern.check(element, type_checker)?;
+                    }
+                }
+                // There is never a value to take apart
+                Type::Never => {
+                    for pattern in patterns.iter_mut() {
+                        pattern.check(&Type::Never, type_checker)?;
+                    }
+                }
+                t => {
+                    return Err(TypeCheckError::TupleArity {
+                        expected: patterns.len(),
+                        actual: Box::new(t),
+                        location: location.clone(),
+                    });
+                }
+            },
+        }
+        Ok(())
+    }
+
+    // The bindings the pattern declares
+    fn locals(&self) -> Vec<usize> {
+        match self {
+            Pattern::Binding(_, local, _) => vec![*local],
+            Pattern::Wildcard => Vec::new(),
+            Pattern::Tuple(patterns, _) => patterns.iter().flat_map(Pattern::locals).collect(),
+        }
+    }
+
+    fn evaluate(&self, value: Value, evaluator: &mut Evaluator) {
+        match (self, value) {
+            (Pattern::Binding(_, local, _), value) => evaluator.set_variable(*local, value),
+            (Pattern::Wildcard, _) => (),
+            (Pattern::Tuple(patterns, _), Value::Tuple(elements)) => {
+                for (pattern, element) in patterns.iter().zip(elements) {
+                    pattern.evaluate(element, evaluator);
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn gen_ir(&self, value: String, t: &Type, ir_generator: &mut IrGenerator) {
+        match self {
+            Pattern::Binding(_, local, _) => {
+                let t = ir_generator.resolve(t);
+                if t == Type::Never {
+                    return;
+                }
+                let address = ir_generator.new_variable(*local, &t);
+                ir_generator.emit(&format!(
+                    "store {} {}, {}* {}",
+                    t.to_ir(),
+                    value,
+                    t.to_ir(),
+                    address
+                ));
+            }
+            Pattern::Wildcard => ir_generator.drop_value(&value, t),
+            Pattern::Tuple(patterns, _) => {
+                let t = ir_generator.resolve(t);
+                let Type::Tuple(elements) = &t else {
+                    for pattern in patterns {
+                        pattern.gen_ir("undef".to_string(), &Type::Never, ir_generator);
+                    }
+                    return;
+                };
+                for (i, (pattern, element)) in patterns.iter().zip(elements).enumerate() {
+                    let result = ir_generator.new_value();
+                    ir_generator.emit(&format!(
+                        "%{} = extractvalue {} {}, {}",
+                        result,
+                        t.to_ir(),
+                        value,
+                        i
+                    ));
+                    pattern.gen_ir(format!("%{}", result), element, ir_generator);
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Let {
+    pattern: Pattern,
+    // The type written after the pattern and where it was written
+    annotation: Option<(Type, Location)>,
+    value: Box<Statement>,
+    r#type: Type,
+}
+
+impl Instruction for Let {
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        parser.bump();
+        let pattern = Pattern::parse(parser)?;
+        let annotation = if let Some(Token {
+            kind: TokenKind::Colon,
+            ..
+        }) = parser.peek()
+        {
+            parser.bump();
+            let start = parser.peek().ok_or(ParseError::UnexpectedEOF)?.location;
+            let t = parser.expect_type()?;
+            Some((t, start.to(&parser.previous().location)))
+        } else {
+            None
+        };
+        parser.expect(&TokenKind::Equals)?;
+        let value = Box::new(Statement::parse_expression(parser)?);
+        Ok(Self {
+            pattern,
+            annotation,
+            value,
+            r#type: Type::UNIT,
+        })
+    }
+
+    fn check(&mut self, type_checker: &mut TypeChecker) -> Result<Type, TypeCheckError> {
+        let mark = type_checker.mark();
+        let t = self.value.check(type_checker)?;
+        let diverges = t == Type::Never;
+        self.r#type = match &mut self.annotation {
+            Some((annotation, location)) => {
+                type_checker.check_type(annotation, location)?;
+                type_checker.unify(annotation, &t, &self.value.location, Some(location))?;
+                annotation.clone()
+            }
+            None => t,
+        };
+        self.pattern.check(&self.r#type, type_checker)?;
+        // The bindings keep what the references in the value borrow in use
+        if self.r#type.contains_reference() {
+            let loans = type_checker.provenance(&mark);
+            for local in self.pattern.locals() {
+                type_checker.derive(local, loans.clone());
+            }
+        }
+        if diverges {
+            Ok(Type::Never)
+        } else {
+            Ok(Type::UNIT)
+        }
+    }
+
+    fn gen_ir(&self, ir_generator: &mut IrGenerator) {
+        self.value.gen_ir(ir_generator);
+        let value = ir_generator.pop_value();
+        self.pattern.gen_ir(value, &self.r#type, ir_generator);
+    }
+    fn evaluate(&self, evaluator: &mut Evaluator) -> Result<Value, Interrupt> {
+        let value = self.value.evaluate(evaluator)?;
+        self.pattern.evaluate(value, evaluator);
+        Ok(Value::UNIT)
+    }
+}
+
+impl Let {
+    pub fn locals(&self) -> Vec<usize> {
+        self.pattern.locals()
+    }
+}
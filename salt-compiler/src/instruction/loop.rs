@@ -1,37 +1,65 @@
 use crate::{
     error::{ParseError, TypeCheckError},
+    evaluator::{Evaluator, Interrupt, Value},
     ir_generator::IrGenerator,
     lexer::Type,
     parser::{Parser, Statement},
     type_checker::TypeChecker,
 };
 
+use super::Instruction;
+
 #[derive(Debug, Clone)]
 pub struct Loop {
     pub body: Box<Statement>,
+    breaks: bool,
 }
 
-impl super::Instruction for Loop {
+impl Instruction for Loop {
     fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
         parser.bump();
-        let body = Box::new(Statement::parse(parser)?);
-        Ok(Self { body })
+        let body = Box::new(Statement::parse_expression(parser)?);
+        Ok(Self {
+            body,
+            breaks: false,
+        })
     }
 
-    fn check(&self, type_checker: &mut TypeChecker) -> Result<Type, TypeCheckError> {
-        self.body.check(type_checker)
+    // Only a `break` gets out of a loop, a body that finishes runs again
+    fn check(&mut self, type_checker: &mut TypeChecker) -> Result<Type, TypeCheckError> {
+        type_checker.new_loop();
+        let t = self.body.check(type_checker)?;
+        self.breaks = type_checker.finish_loop(t != Type::Never)?;
+        if self.breaks {
+            Ok(Type::UNIT)
+        } else {
+            Ok(Type::Never)
+        }
     }
 
     fn gen_ir(&self, ir_generator: &mut IrGenerator) {
-        let mut ir = String::new();
         let loop_nr = ir_generator.new_loop();
-        ir.push_str(&format!("br label %loop{}\n", loop_nr));
-        ir.push_str(&format!("loop{}:\n", loop_nr));
+        ir_generator.emit(&format!("br label %loop{}", loop_nr));
+        ir_generator.emit(&format!("loop{}:", loop_nr));
+        ir_generator.loops.push(loop_nr);
         self.body.gen_ir(ir_generator);
-        ir.push_str(&ir_generator.pop_stash());
-        ir.push_str(&format!("br label %loop{}\n", loop_nr));
-        ir.push_str(&format!("loop_exit{}:", loop_nr));
+        ir_generator.loops.pop();
+        ir_generator.pop_value();
+        ir_generator.emit(&format!("br label %loop{}", loop_nr));
+        ir_generator.emit(&format!("loop_exit{}:", loop_nr));
+        ir_generator.value = match self.breaks {
+            true => "zeroinitializer".to_string(),
+            false => "undef".to_string(),
+        };
+    }
 
-        ir_generator.stash = ir;
+    fn evaluate(&self, evaluator: &mut Evaluator) -> Result<Value, Interrupt> {
+        loop {
+            match self.body.evaluate(evaluator) {
+                Ok(_) => (),
+                Err(Interrupt::Break) => return Ok(Value::UNIT),
+                Err(interrupt) => return Err(interrupt),
+            }
+        }
     }
 }
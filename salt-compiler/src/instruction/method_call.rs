@@ -0,0 +1,96 @@
+use crate::{
+    error::{ParseError, TypeCheckError},
+    evaluator::{Evaluator, Interrupt, Value},
+    ir_generator::IrGenerator,
+    lexer::{IntType, Token, TokenKind, Type},
+    parser::{Parser, Statement},
+    type_checker::TypeChecker,
+};
+
+use super::Instruction;
+
+#[derive(Debug, Clone)]
+pub struct MethodCall {
+    pub value: Box<Statement>,
+    name: String,
+    arguments: Vec<Statement>,
+    receiver: Type,
+}
+
+impl Instruction for MethodCall {
+    // Parses the `.name(...)` after a value, the caller fills in the value it belongs to
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        parser.expect(&TokenKind::Dot)?;
+        let name = parser.expect_ident()?;
+        parser.expect(&TokenKind::OpenParen)?;
+        let mut arguments = Vec::new();
+        while let Some(token) = parser.peek()
+            && token.kind != TokenKind::CloseParen
+        {
+            arguments.push(Statement::parse_expression(parser)?);
+            if let Some(Token {
+                kind: TokenKind::Comma,
+                ..
+            }) = parser.peek()
+            {
+                parser.bump();
+            } else {
+                break;
+            }
+        }
+        parser.expect(&TokenKind::CloseParen)?;
+        Ok(Self {
+            value: Box::new(Statement::EMPTY),
+            name,
+            arguments,
+            receiver: Type::UNIT,
+        })
+    }
+
+    // Only the methods built into the primitive types exist so far
+    fn check(&mut self, type_checker: &mut TypeChecker) -> Result<Type, TypeCheckError> {
+        let t = self.value.check(type_checker)?;
+        self.receiver = type_checker.resolve(&t);
+        match (&self.receiver, self.name.as_str()) {
+            (Type::Str | Type::ByteStr, "len") => {
+                if !self.arguments.is_empty() {
+                    return Err(TypeCheckError::ArgumentCountNoToken {
+                        expected: 0,
+                        actual: self.arguments.len(),
+                        variadic: false,
+                    });
+                }
+                Ok(Type::Int(IntType::Usize))
+            }
+            _ => Err(TypeCheckError::NoMethodNoToken {
+                r#type: Box::new(self.receiver.clone()),
+                name: self.name.as_str().into(),
+            }),
+        }
+    }
+
+    fn gen_ir(&self, ir_generator: &mut IrGenerator) {
+        self.value.gen_ir(ir_generator);
+        let value = ir_generator.pop_value();
+        match self.name.as_str() {
+            "len" => {
+                let length = ir_generator.new_value();
+                ir_generator.emit(&format!(
+                    "%{} = extractvalue {} {}, 1",
+                    length,
+                    self.receiver.to_ir(),
+                    value
+                ));
+                ir_generator.value = format!("%{}", length);
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn evaluate(&self, evaluator: &mut Evaluator) -> Result<Value, Interrupt> {
+        let Value::Bytes(bytes) = self.value.evaluate(evaluator)? else {
+            unreachable!()
+        };
+        Ok(Value::Int(bytes.len() as u128))
+    }
+}
@@ -1,24 +1,60 @@
 use crate::{
-    error::{ParseError, TypeCheckError},
+    error::{EvaluationError, ParseError, TypeCheckError},
+    evaluator::{Evaluator, Interrupt, Value},
     ir_generator::IrGenerator,
     lexer::Type,
     parser::Parser,
     type_checker::TypeChecker,
 };
 
+pub mod assert;
+pub mod assignment;
+pub mod attribute;
+pub mod binary;
 pub mod block;
+pub mod bool_literal;
+pub mod r#break;
+pub mod byte_literal;
+pub mod byte_string_literal;
+pub mod c_string_literal;
+pub mod cast;
+pub mod char_literal;
+pub mod const_declaration;
+pub mod defer;
+pub mod deref;
+pub mod extern_block;
+pub mod field;
 pub mod function_call;
 pub mod function_declaration;
+pub mod r#impl;
+pub mod integer_literal;
 pub mod intrinsic;
+pub mod r#let;
 pub mod r#loop;
+pub mod method_call;
+pub mod negate;
+pub mod panic;
+pub mod print;
+pub mod raw_block;
+pub mod reference;
+pub mod r#return;
+pub mod slice;
 pub mod string_literal;
+pub mod struct_declaration;
+pub mod tuple;
 pub mod r#use;
+pub mod variable;
 
 pub trait Instruction
 where
     Self: std::marker::Sized,
 {
     fn parse(parser: &mut Parser) -> Result<Self, ParseError>;
-    fn check(&self, type_checker: &mut TypeChecker) -> Result<Type, TypeCheckError>;
+    fn check(&mut self, type_checker: &mut TypeChecker) -> Result<Type, TypeCheckError>;
     fn gen_ir(&self, ir_generator: &mut IrGenerator);
+
+    // Works out the value at compile time, for the instructions a `const fn` can use
+    fn evaluate(&self, _evaluator: &mut Evaluator) -> Result<Value, Interrupt> {
+        Err(EvaluationError::NotConstNoToken.into())
+    }
 }
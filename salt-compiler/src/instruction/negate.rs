@@ -0,0 +1,100 @@
+use crate::{
+    error::{EvaluationError, ParseError, TypeCheckError},
+    evaluator::{self, Evaluator, Interrupt, Value},
+    ir_generator::IrGenerator,
+    lexer::{Location, TokenKind, Type},
+    parser::{Parser, Statement},
+    type_checker::TypeChecker,
+};
+
+use super::{Instruction, binary};
+
+#[derive(Debug, Clone)]
+pub struct Negate {
+    pub value: Box<Statement>,
+    location: Location,
+    r#type: Type,
+}
+
+impl Instruction for Negate {
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        parser.expect(&TokenKind::Minus)?;
+        let start = parser.previous().location.clone();
+        let value = Box::new(Statement::parse_unary(parser)?);
+        Ok(Self {
+            value,
+            location: start.to(&parser.previous().location),
+            r#type: Type::UNIT,
+        })
+    }
+
+    // Only signed integers can be negated, an integer whose type is not decided yet is checked
+    // once it is
+    fn check(&mut self, type_checker: &mut TypeChecker) -> Result<Type, TypeCheckError> {
+        let t = self.value.check(type_checker)?;
+        match type_checker.resolve(&t) {
+            Type::Int(int) if int.signed() => (),
+            Type::Int(_) => {
+                return Err(TypeCheckError::NegateUnsigned {
+                    location: self.value.location.clone(),
+                    r#type: Box::new(t),
+                });
+            }
+            Type::IntVar(_) => type_checker.new_negation(&t, &self.value.location),
+            Type::Never => (),
+            _ => {
+                return Err(TypeCheckError::InvalidOperand {
+                    location: self.value.location.clone(),
+                    r#type: Box::new(t),
+                    operator: "-",
+                });
+            }
+        }
+        self.r#type = t.clone();
+        Ok(t)
+    }
+
+    // Negating the smallest value is the only way to overflow
+    fn gen_ir(&self, ir_generator: &mut IrGenerator) {
+        self.value.gen_ir(ir_generator);
+        let value = ir_generator.pop_value();
+        let Type::Int(int) = ir_generator.resolve(&self.r#type) else {
+            ir_generator.value = "undef".to_string();
+            return;
+        };
+        if ir_generator.release {
+            let result = ir_generator.new_value();
+            ir_generator.emit(&format!(
+                "%{} = sub {} 0, {}",
+                result,
+                Type::Int(int).to_ir(),
+                value
+            ));
+            ir_generator.value = format!("%{}", result);
+            return;
+        }
+        ir_generator.value = binary::checked(
+            ir_generator,
+            "ssub",
+            int,
+            "0",
+            &value,
+            "attempt to negate with overflow",
+            &self.location,
+        );
+    }
+
+    fn evaluate(&self, evaluator: &mut Evaluator) -> Result<Value, Interrupt> {
+        let value = self.value.evaluate(evaluator)?;
+        let (Type::Int(int), Value::Int(bits)) = (evaluator.resolve(&self.r#type), value) else {
+            unreachable!()
+        };
+        match evaluator::from_i128(-evaluator::to_i128(bits, int), int) {
+            (bits, false) => Ok(Value::Int(bits)),
+            (_, true) => Err(EvaluationError::PanicNoToken {
+                message: "attempt to negate with overflow".into(),
+            }
+            .into()),
+        }
+    }
+}
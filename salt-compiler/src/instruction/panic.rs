@@ -0,0 +1,58 @@
+use crate::{
+    error::{EvaluationError, ParseError, TypeCheckError},
+    evaluator::{Evaluator, Interrupt, Value},
+    ir_generator::IrGenerator,
+    lexer::{Location, TokenKind, Type},
+    parser::{Parser, Statement},
+    type_checker::TypeChecker,
+};
+
+use super::Instruction;
+
+#[derive(Debug, Clone)]
+pub struct Panic {
+    pub message: Box<Statement>,
+    // The file, line and column the panic handler is given
+    pub location: Location,
+}
+
+impl Instruction for Panic {
+    // `panic("message")`
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        parser.expect_ident()?;
+        let start = parser.previous().location.clone();
+        parser.expect(&TokenKind::OpenParen)?;
+        let message = Box::new(Statement::parse_expression(parser)?);
+        parser.expect(&TokenKind::CloseParen)?;
+        Ok(Self {
+            message,
+            location: start.to(&parser.previous().location),
+        })
+    }
+
+    fn check(&mut self, type_checker: &mut TypeChecker) -> Result<Type, TypeCheckError> {
+        let t = self.message.check(type_checker)?;
+        type_checker.unify(&Type::Str, &t, &self.message.location, None)?;
+        Ok(Type::Never)
+    }
+
+    // The panic handler never returns, so the code after it can not be reached
+    fn gen_ir(&self, ir_generator: &mut IrGenerator) {
+        self.message.gen_ir(ir_generator);
+        let message = ir_generator.pop_value();
+        ir_generator.call_panic_handler(&message, &self.location);
+        ir_generator.unreachable("unreachable");
+    }
+
+    // A panic while a constant is worked out is an error with the message
+    fn evaluate(&self, evaluator: &mut Evaluator) -> Result<Value, Interrupt> {
+        let Value::Bytes(message) = self.message.evaluate(evaluator)? else {
+            unreachable!()
+        };
+        Err(EvaluationError::Panic {
+            message: String::from_utf8_lossy(&message).into(),
+            location: self.location.clone(),
+        }
+        .into())
+    }
+}
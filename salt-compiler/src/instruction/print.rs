@@ -0,0 +1,233 @@
+use crate::{
+    error::{ExpectedToken, ParseError, TypeCheckError},
+    ir_generator::IrGenerator,
+    lexer::{Location, Token, TokenKind, Type},
+    parser::{Parser, Statement},
+    type_checker::TypeChecker,
+};
+
+use super::Instruction;
+
+// The module the functions `print!` calls are in
+const MODULE: &str = "std::io::vga";
+
+#[derive(Debug, Clone)]
+pub struct Print {
+    pieces: Vec<Piece>,
+    // The format string
+    location: Location,
+    arguments: Vec<Statement>,
+    types: Vec<Type>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Piece {
+    Text(String),
+    // `{}`, an integer in decimal or a `str` as it is
+    Display,
+    // `{:x}`, an integer in hexadecimal
+    Hex,
+}
+
+impl Instruction for Print {
+    // `print!("x = {} at {:x}", a, b)`, the format string is taken apart here
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        parser.bump();
+        parser.expect(&TokenKind::Bang)?;
+        parser.expect(&TokenKind::OpenParen)?;
+        let (format, location) = match parser.bump() {
+            Some(Token {
+                kind: TokenKind::StringLiteral(format),
+                location,
+                ..
+            }) => (format, location),
+            Some(token) => Err(ParseError::UnexpectedToken {
+                actual: Box::new(token),
+                expected: ExpectedToken::FormatString,
+            })?,
+            None => Err(ParseError::UnexpectedEOF)?,
+        };
+        let pieces =
+            parse_format(&format).map_err(|placeholder| ParseError::InvalidPlaceholder {
+                location: location.clone(),
+                placeholder: placeholder.into(),
+            })?;
+
+        let mut arguments = Vec::new();
+        while let Some(Token {
+            kind: TokenKind::Comma,
+            ..
+        }) = parser.peek()
+        {
+            parser.bump();
+            if let Some(TokenKind::CloseParen) = parser.peek().map(|token| token.kind) {
+                break;
+            }
+            arguments.push(Statement::parse_expression(parser)?);
+        }
+        parser.expect(&TokenKind::CloseParen)?;
+
+        let placeholders = pieces
+            .iter()
+            .filter(|piece| !matches!(piece, Piece::Text(_)))
+            .count();
+        if placeholders != arguments.len() {
+            return Err(ParseError::FormatArguments {
+                location,
+                placeholders,
+                arguments: arguments.len(),
+            });
+        }
+
+        Ok(Self {
+            pieces,
+            location,
+            arguments,
+            types: Vec::new(),
+        })
+    }
+
+    // Every placeholder only takes the types there is a function to print them with
+    fn check(&mut self, type_checker: &mut TypeChecker) -> Result<Type, TypeCheckError> {
+        if type_checker.in_const() {
+            return Err(TypeCheckError::NotConstCallNoToken);
+        }
+        self.types.clear();
+        let placeholders = self
+            .pieces
+            .iter()
+            .filter(|piece| !matches!(piece, Piece::Text(_)));
+        for (argument, piece) in self.arguments.iter_mut().zip(placeholders) {
+            let t = argument.check(type_checker)?;
+            let t = type_checker.default_integer(&t, &argument.location);
+            match (&t, piece) {
+                (Type::Int(_), _) | (Type::Str, Piece::Display) => (),
+                _ => {
+                    return Err(TypeCheckError::NotPrintable {
+                        location: argument.location.clone(),
+                        r#type: Box::new(t),
+                        hex: *piece == Piece::Hex,
+                        format: Box::new(self.location.clone()),
+                    });
+                }
+            }
+            self.types.push(t);
+        }
+        for name in ["print", "print_u64", "print_i64", "print_hex"] {
+            type_checker.stdlib_function(MODULE, name, &self.location)?;
+        }
+        Ok(Type::UNIT)
+    }
+
+    // The arguments are worked out first, then every piece is printed by its own call
+    fn gen_ir(&self, ir_generator: &mut IrGenerator) {
+        let mut values = Vec::new();
+        for argument in &self.arguments {
+            argument.gen_ir(ir_generator);
+            values.push(ir_generator.pop_value());
+        }
+
+        let mut values = values.into_iter().zip(&self.types);
+        for piece in &self.pieces {
+            let (function, argument) = match piece {
+                Piece::Text(text) => (
+                    "print",
+                    format!(
+                        "i8* {}, i64 {}",
+                        ir_generator.new_string(text.as_bytes()),
+                        text.len()
+                    ),
+                ),
+                Piece::Display | Piece::Hex => {
+                    let (value, t) = values.next().unwrap();
+                    match (ir_generator.resolve(t), piece) {
+                        (Type::Str, _) => {
+                            let mut parts = Vec::new();
+                            for (i, part) in ["i8*", "i64"].into_iter().enumerate() {
+                                let result = ir_generator.new_value();
+                                ir_generator.emit(&format!(
+                                    "%{} = extractvalue {} {}, {}",
+                                    result,
+                                    Type::Str.to_ir(),
+                                    value,
+                                    i
+                                ));
+                                parts.push(format!("{} %{}", part, result));
+                            }
+                            ("print", parts.join(", "))
+                        }
+                        // Hexadecimal shows the bits of a signed integer as they are
+                        (Type::Int(int), piece) => {
+                            let (function, extend) = match piece {
+                                Piece::Hex => ("print_hex", "zext"),
+                                _ if int.signed() => ("print_i64", "sext"),
+                                _ => ("print_u64", "zext"),
+                            };
+                            let value = if int.bits() < 64 {
+                                let result = ir_generator.new_value();
+                                ir_generator.emit(&format!(
+                                    "%{} = {} {} {} to i64",
+                                    result,
+                                    extend,
+                                    Type::Int(int).to_ir(),
+                                    value
+                                ));
+                                format!("%{}", result)
+                            } else {
+                                value
+                            };
+                            (function, format!("i64 {}", value))
+                        }
+                        _ => unreachable!(),
+                    }
+                }
+            };
+            let signature = ir_generator.env[&format!("{MODULE}::{function}")].clone();
+            ir_generator.declare(&signature);
+            ir_generator.emit(&format!("call void @{}({})", signature.symbol, argument));
+        }
+        ir_generator.value = "zeroinitializer".to_string();
+    }
+}
+
+// Splits the format string into text and placeholders, `{{` and `}}` are braces in the text.
+// Returns the placeholder that is not valid
+fn parse_format(format: &str) -> Result<Vec<Piece>, String> {
+    let mut pieces = Vec::new();
+    let mut text = String::new();
+    let mut chars = format.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' | '}' if chars.peek() == Some(&c) => {
+                chars.next();
+                text.push(c);
+            }
+            '{' => {
+                let mut placeholder = String::from('{');
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => placeholder.push(c),
+                        None => return Err(placeholder),
+                    }
+                }
+                placeholder.push('}');
+                let piece = match placeholder.as_str() {
+                    "{}" => Piece::Display,
+                    "{:x}" => Piece::Hex,
+                    _ => return Err(placeholder),
+                };
+                if !text.is_empty() {
+                    pieces.push(Piece::Text(std::mem::take(&mut text)));
+                }
+                pieces.push(piece);
+            }
+            '}' => return Err(String::from('}')),
+            c => text.push(c),
+        }
+    }
+    if !text.is_empty() {
+        pieces.push(Piece::Text(text));
+    }
+    Ok(pieces)
+}
@@ -0,0 +1,55 @@
+use crate::{
+    error::{ParseError, TypeCheckError, Warning},
+    evaluator::{Evaluator, Interrupt, Value},
+    ir_generator::IrGenerator,
+    lexer::{Keyword, Location, TokenKind, Type},
+    parser::{Parser, Statement, StatementKind},
+    type_checker::TypeChecker,
+};
+
+use super::{Instruction, block::Block};
+
+#[derive(Debug, Clone)]
+pub struct RawBlock {
+    pub body: Box<Statement>,
+    // The `raw` keyword
+    location: Location,
+}
+
+impl Instruction for RawBlock {
+    // `raw { ... }`, a block that can do what a `raw fn` can
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        parser.expect(&TokenKind::Keyword(Keyword::Raw))?;
+        let location = parser.previous().location.clone();
+        let start = parser.peek().ok_or(ParseError::UnexpectedEOF)?.location;
+        let block = Block::parse(parser)?;
+        Ok(Self {
+            body: Box::new(Statement {
+                kind: StatementKind::Block(block),
+                location: start,
+                returns: false,
+            }),
+            location,
+        })
+    }
+
+    // Only the body allows unsafe operations, a body without any gets a warning
+    fn check(&mut self, type_checker: &mut TypeChecker) -> Result<Type, TypeCheckError> {
+        type_checker.new_raw();
+        let t = self.body.check(type_checker);
+        if !type_checker.finish_raw() && t.is_ok() {
+            type_checker
+                .warnings
+                .push(Warning::UnusedRaw(self.location.clone()));
+        }
+        t
+    }
+
+    fn gen_ir(&self, ir_generator: &mut IrGenerator) {
+        self.body.gen_ir(ir_generator);
+    }
+
+    fn evaluate(&self, evaluator: &mut Evaluator) -> Result<Value, Interrupt> {
+        self.body.evaluate(evaluator)
+    }
+}
@@ -0,0 +1,59 @@
+use crate::{
+    error::{ParseError, TypeCheckError},
+    ir_generator::IrGenerator,
+    lexer::{Keyword, Location, TokenKind, Type},
+    parser::{Parser, Statement},
+    place,
+    type_checker::{Access, TypeChecker},
+};
+
+#[derive(Debug, Clone)]
+pub struct Reference {
+    mutable: bool,
+    pub value: Box<Statement>,
+    location: Location,
+}
+
+impl super::Instruction for Reference {
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        parser.bump();
+        let start = parser.previous().location.clone();
+        let mutable = parser
+            .peek()
+            .is_some_and(|token| token.kind == TokenKind::Keyword(Keyword::Mut));
+        if mutable {
+            parser.bump();
+        }
+        let value = Box::new(Statement::parse_unary(parser)?);
+        Ok(Self {
+            mutable,
+            value,
+            location: start.to(&parser.previous().location),
+        })
+    }
+
+    // There can be many shared references to a place or a single mutable one, the reference
+    // borrows the binding the place is in for as long as it is in use
+    fn check(&mut self, type_checker: &mut TypeChecker) -> Result<Type, TypeCheckError> {
+        let place = place::check_place(&mut self.value, type_checker)?;
+        if self.mutable && !place.mutable {
+            return Err(TypeCheckError::ImmutablePlace(self.value.location.clone()));
+        }
+        if let Some(root) = place.root {
+            let access = match self.mutable {
+                true => Access::BorrowMut,
+                false => Access::Borrow,
+            };
+            type_checker.access(root, access, &place.location)?;
+            type_checker.new_loan(root, self.mutable, place.through_deref, &self.location);
+        }
+        Ok(Type::Reference {
+            mutable: self.mutable,
+            referent: Box::new(place.r#type),
+        })
+    }
+
+    fn gen_ir(&self, ir_generator: &mut IrGenerator) {
+        ir_generator.value = place::gen_address(&self.value, ir_generator);
+    }
+}
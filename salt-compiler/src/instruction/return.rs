@@ -0,0 +1,82 @@
+use crate::{
+    error::{EvaluationError, ParseError, TypeCheckError},
+    evaluator::{Evaluator, Interrupt, Value},
+    ir_generator::IrGenerator,
+    lexer::{Location, TokenKind, Type},
+    parser::{Parser, Statement},
+    type_checker::{Cleanup, TypeChecker},
+};
+
+use super::Instruction;
+
+#[derive(Debug, Clone)]
+pub struct Return {
+    value: Option<Box<Statement>>,
+    location: Location,
+    r#type: Type,
+    // Everything in the function that has to be cleaned up, after the value is computed
+    cleanup: Vec<Cleanup>,
+}
+
+impl Instruction for Return {
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        parser.bump();
+        let location = parser.previous().location.clone();
+        let value = match parser.peek().map(|token| token.kind) {
+            Some(TokenKind::Semicolon | TokenKind::CloseBrace) | None => None,
+            _ => Some(Box::new(Statement::parse_expression(parser)?)),
+        };
+        Ok(Self {
+            value,
+            location,
+            r#type: Type::UNIT,
+            cleanup: Vec::new(),
+        })
+    }
+
+    fn check(&mut self, type_checker: &mut TypeChecker) -> Result<Type, TypeCheckError> {
+        let Some((return_type, origin)) = type_checker.return_type().cloned() else {
+            return Err(TypeCheckError::ReturnOutsideFunctionNoToken);
+        };
+        if let Some(value) = &mut self.value {
+            let mark = type_checker.mark();
+            let t = value.check(type_checker)?;
+            type_checker.unify(&return_type, &t, &value.location, Some(&origin))?;
+            if return_type.contains_reference() {
+                type_checker.check_return(&type_checker.provenance(&mark))?;
+            }
+        } else {
+            type_checker.unify(&return_type, &Type::UNIT, &self.location, Some(&origin))?;
+        }
+        self.r#type = return_type;
+        self.cleanup = type_checker.return_cleanup()?;
+        Ok(Type::Never)
+    }
+
+    fn gen_ir(&self, ir_generator: &mut IrGenerator) {
+        let value = match &self.value {
+            Some(value) => {
+                value.gen_ir(ir_generator);
+                ir_generator.pop_value()
+            }
+            None => "zeroinitializer".to_string(),
+        };
+        ir_generator.clean_up(&self.cleanup);
+        if self.r#type == Type::UNIT {
+            ir_generator.unreachable("ret void");
+        } else {
+            ir_generator.unreachable(&format!("ret {} {}", self.r#type.to_ir(), value));
+        }
+    }
+
+    fn evaluate(&self, evaluator: &mut Evaluator) -> Result<Value, Interrupt> {
+        let value = match &self.value {
+            Some(value) => value.evaluate(evaluator)?,
+            None => Value::UNIT,
+        };
+        if !self.cleanup.is_empty() {
+            return Err(EvaluationError::NotConstNoToken.into());
+        }
+        Err(Interrupt::Return(value))
+    }
+}
@@ -0,0 +1,146 @@
+use crate::{
+    error::{EvaluationError, ParseError, TypeCheckError},
+    evaluator::{Evaluator, Interrupt, Value},
+    ir_generator::IrGenerator,
+    lexer::{IntType, Location, TokenKind, Type},
+    parser::{Parser, Statement},
+    type_checker::TypeChecker,
+};
+
+use super::Instruction;
+
+#[derive(Debug, Clone)]
+pub struct Slice {
+    pub value: Box<Statement>,
+    // Missing bounds are the start and the end of the value
+    start: Option<Box<Statement>>,
+    end: Option<Box<Statement>>,
+    // The whole expression, which is where it panics
+    pub location: Location,
+    r#type: Type,
+}
+
+impl Instruction for Slice {
+    // Parses the `[a..b]` after a value, the caller fills in the value it belongs to
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        parser.expect(&TokenKind::OpenBracket)?;
+        let start = match parser.peek().map(|token| token.kind) {
+            Some(TokenKind::DotDot) => None,
+            _ => Some(Box::new(Statement::parse_expression(parser)?)),
+        };
+        parser.expect(&TokenKind::DotDot)?;
+        let end = match parser.peek().map(|token| token.kind) {
+            Some(TokenKind::CloseBracket) => None,
+            _ => Some(Box::new(Statement::parse_expression(parser)?)),
+        };
+        parser.expect(&TokenKind::CloseBracket)?;
+        Ok(Self {
+            value: Box::new(Statement::EMPTY),
+            start,
+            end,
+            location: Location::default(),
+            r#type: Type::UNIT,
+        })
+    }
+
+    // The bounds are byte offsets into the value
+    fn check(&mut self, type_checker: &mut TypeChecker) -> Result<Type, TypeCheckError> {
+        let t = self.value.check(type_checker)?;
+        self.r#type = type_checker.resolve(&t);
+        if !matches!(self.r#type, Type::Str | Type::ByteStr) {
+            return Err(TypeCheckError::NotSliceableNoToken {
+                r#type: Box::new(self.r#type.clone()),
+            });
+        }
+        for bound in [&mut self.start, &mut self.end].into_iter().flatten() {
+            let t = bound.check(type_checker)?;
+            type_checker.unify(&Type::Int(IntType::Usize), &t, &bound.location, None)?;
+        }
+        Ok(self.r#type.clone())
+    }
+
+    // A range that is reversed or goes past the end panics, in release builds too
+    fn gen_ir(&self, ir_generator: &mut IrGenerator) {
+        let t = self.r#type.to_ir();
+        self.value.gen_ir(ir_generator);
+        let value = ir_generator.pop_value();
+
+        let pointer = ir_generator.new_value();
+        ir_generator.emit(&format!("%{} = extractvalue {} {}, 0", pointer, t, value));
+        let start = match &self.start {
+            Some(start) => {
+                start.gen_ir(ir_generator);
+                ir_generator.pop_value()
+            }
+            None => "0".to_string(),
+        };
+        let length = ir_generator.new_value();
+        ir_generator.emit(&format!("%{} = extractvalue {} {}, 1", length, t, value));
+        let length = format!("%{}", length);
+        let end = match &self.end {
+            Some(end) => {
+                end.gen_ir(ir_generator);
+                ir_generator.pop_value()
+            }
+            None => length.clone(),
+        };
+
+        for (condition, message) in [
+            ((&start, &end), "slice index starts after it ends"),
+            (
+                (&end, &length),
+                "slice index ends past the end of the slice",
+            ),
+        ] {
+            let past = ir_generator.new_value();
+            ir_generator.emit(&format!(
+                "%{} = icmp ugt i64 {}, {}",
+                past, condition.0, condition.1
+            ));
+            ir_generator.panic_if(&format!("%{}", past), message, &self.location);
+        }
+
+        let new_pointer = ir_generator.new_value();
+        ir_generator.emit(&format!(
+            "%{} = getelementptr i8, i8* %{}, i64 {}",
+            new_pointer, pointer, start
+        ));
+        let new_length = ir_generator.new_value();
+        ir_generator.emit(&format!("%{} = sub i64 {}, {}", new_length, end, start));
+        let slice = ir_generator.new_value();
+        ir_generator.emit(&format!(
+            "%{} = insertvalue {} undef, i8* %{}, 0",
+            slice, t, new_pointer
+        ));
+        let result = ir_generator.new_value();
+        ir_generator.emit(&format!(
+            "%{} = insertvalue {} %{}, i64 %{}, 1",
+            result, t, slice, new_length
+        ));
+        ir_generator.value = format!("%{}", result);
+    }
+
+    // A range that does not fit in the value is an error instead of a panic
+    fn evaluate(&self, evaluator: &mut Evaluator) -> Result<Value, Interrupt> {
+        let Value::Bytes(bytes) = self.value.evaluate(evaluator)? else {
+            unreachable!()
+        };
+        let mut bounds = [0, bytes.len() as u128];
+        for (bound, value) in [&self.start, &self.end].into_iter().zip(&mut bounds) {
+            if let Some(bound) = bound {
+                let Value::Int(bits) = bound.evaluate(evaluator)? else {
+                    unreachable!()
+                };
+                *value = bits;
+            }
+        }
+        let [start, end] = bounds;
+        if start > end || end > bytes.len() as u128 {
+            return Err(EvaluationError::OutOfBoundsNoToken {
+                length: bytes.len(),
+            }
+            .into());
+        }
+        Ok(Value::Bytes(bytes[start as usize..end as usize].to_vec()))
+    }
+}
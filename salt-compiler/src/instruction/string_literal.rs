@@ -1,5 +1,6 @@
 use crate::{
     error::{ParseError, TypeCheckError},
+    evaluator::{Evaluator, Interrupt, Value},
     ir_generator::IrGenerator,
     lexer::{TokenKind, Type},
     parser::Parser,
@@ -20,29 +21,15 @@ impl super::Instruction for StringLiteral {
         }
     }
 
-    fn check(&self, _type_checker: &mut TypeChecker) -> Result<Type, TypeCheckError> {
+    fn check(&mut self, _type_checker: &mut TypeChecker) -> Result<Type, TypeCheckError> {
         Ok(Type::Str)
     }
 
     fn gen_ir(&self, ir_generator: &mut IrGenerator) {
-        let str_num = ir_generator.strings.len();
-        ir_generator.strings.push(self.clone());
-        ir_generator.stash = format!(
-            "getelementptr [{} x i8], [{} x i8]* @.str.{}, i64 0, i64 0",
-            self.value.len() + 1,
-            self.value.len() + 1,
-            str_num
-        );
+        ir_generator.value = ir_generator.new_slice(self.value.as_bytes());
     }
-}
 
-impl StringLiteral {
-    pub fn to_ir(&self, str_num: usize) -> String {
-        format!(
-            "@.str.{} = internal constant [{} x i8] c\"{}\\00\"",
-            str_num,
-            self.value.len() + 1,
-            self.value
-        )
+    fn evaluate(&self, _evaluator: &mut Evaluator) -> Result<Value, Interrupt> {
+        Ok(Value::Bytes(self.value.as_bytes().to_vec()))
     }
 }
@@ -0,0 +1,104 @@
+use crate::{
+    error::{ParseError, TypeCheckError},
+    ir_generator::IrGenerator,
+    lexer::{Keyword, Location, Token, TokenKind, Type},
+    mangle::Symbol,
+    parser::Parser,
+    type_checker::{StructSignature, TypeChecker},
+};
+
+use super::attribute::{self, Attribute, Target};
+
+#[derive(Debug, Clone)]
+pub struct StructDeclaration {
+    pub name: String,
+    // The path of its symbol, see `TypeChecker::struct_key`
+    key: String,
+    pub location: Location,
+    pub doc: Vec<String>,
+    pub attributes: Vec<Attribute>,
+    // Fields are known by their index like the elements of a tuple
+    pub fields: Vec<(Type, Location)>,
+}
+
+impl super::Instruction for StructDeclaration {
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        let doc = parser.doc_comments();
+        let attributes = parser.expect_attributes()?;
+        parser.expect(&TokenKind::Keyword(Keyword::Struct))?;
+        let name = parser.expect_ident()?;
+        let location = parser.previous().location.clone();
+
+        // `struct Name;` has no fields, `struct Name(A, B);` has a field of each type
+        let mut fields = Vec::new();
+        if let Some(Token {
+            kind: TokenKind::OpenParen,
+            ..
+        }) = parser.peek()
+        {
+            parser.bump();
+            while let Some(token) = parser.peek()
+                && token.kind != TokenKind::CloseParen
+            {
+                let t = parser.expect_type()?;
+                fields.push((t, token.location.to(&parser.previous().location)));
+                if let Some(Token {
+                    kind: TokenKind::Comma,
+                    ..
+                }) = parser.peek()
+                {
+                    parser.bump();
+                } else {
+                    break;
+                }
+            }
+            parser.expect(&TokenKind::CloseParen)?;
+        }
+        parser.expect(&TokenKind::Semicolon)?;
+
+        Ok(Self {
+            key: name.clone(),
+            name,
+            location,
+            doc,
+            attributes,
+            fields,
+        })
+    }
+
+    fn check(&mut self, type_checker: &mut TypeChecker) -> Result<Type, TypeCheckError> {
+        attribute::check_attributes(&mut self.attributes, Target::Struct, type_checker)?;
+        // A struct has no way to say how long what a reference in it points to lives
+        self.key = type_checker.struct_key(&self.name).unwrap().clone();
+        for (t, location) in &mut self.fields {
+            type_checker.check_type(t, location)?;
+            if t.contains_reference() {
+                return Err(TypeCheckError::ReferenceInStruct(location.clone()));
+            }
+        }
+        Ok(Type::UNIT)
+    }
+
+    fn gen_ir(&self, ir_generator: &mut IrGenerator) {
+        let mut ir = String::new();
+        for line in &self.doc {
+            ir.push_str(&format!(";{line}\n"));
+        }
+        ir.push_str(&format!(
+            "{} = type {}\n",
+            Type::Struct(ir_generator.structs[&self.key].symbol.clone()).to_ir(),
+            Type::Tuple(self.fields.iter().map(|(t, _)| t.clone()).collect()).to_ir()
+        ));
+        ir_generator.function_declarations.push(ir);
+    }
+}
+
+impl StructDeclaration {
+    pub fn signature(&self, symbol: Symbol) -> StructSignature {
+        StructSignature {
+            symbol,
+            fields: self.fields.iter().map(|(t, _)| t.clone()).collect(),
+            drop: None,
+        }
+    }
+}
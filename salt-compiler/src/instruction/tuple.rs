@@ -0,0 +1,87 @@
+use crate::{
+    error::{ParseError, TypeCheckError},
+    evaluator::{Evaluator, Interrupt, Value},
+    ir_generator::IrGenerator,
+    lexer::{Token, TokenKind, Type},
+    parser::{Parser, Statement},
+    type_checker::TypeChecker,
+};
+
+use super::Instruction;
+
+#[derive(Debug, Clone)]
+pub struct Tuple {
+    pub elements: Vec<Statement>,
+    // `(a)` only groups `a`, a tuple with one element is written `(a,)`
+    pub grouping: bool,
+    types: Vec<Type>,
+}
+
+impl Instruction for Tuple {
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        parser.expect(&TokenKind::OpenParen)?;
+        let mut elements = Vec::new();
+        let mut trailing_comma = false;
+        while let Some(token) = parser.peek()
+            && token.kind != TokenKind::CloseParen
+        {
+            elements.push(Statement::parse_expression(parser)?);
+            trailing_comma = false;
+            if let Some(Token {
+                kind: TokenKind::Comma,
+                ..
+            }) = parser.peek()
+            {
+                parser.bump();
+                trailing_comma = true;
+            } else {
+                break;
+            }
+        }
+        parser.expect(&TokenKind::CloseParen)?;
+        Ok(Self {
+            grouping: elements.len() == 1 && !trailing_comma,
+            elements,
+            types: Vec::new(),
+        })
+    }
+
+    fn check(&mut self, type_checker: &mut TypeChecker) -> Result<Type, TypeCheckError> {
+        self.types.clear();
+        for element in &mut self.elements {
+            self.types.push(element.check(type_checker)?);
+        }
+        Ok(Type::Tuple(self.types.clone()))
+    }
+
+    // Fills in the fields of the struct one by one
+    fn gen_ir(&self, ir_generator: &mut IrGenerator) {
+        let t = ir_generator
+            .resolve(&Type::Tuple(self.types.clone()))
+            .to_ir();
+        let mut tuple = String::from("undef");
+        if self.elements.is_empty() {
+            tuple = String::from("zeroinitializer");
+        }
+        for (i, (element, element_type)) in self.elements.iter().zip(&self.types).enumerate() {
+            element.gen_ir(ir_generator);
+            let value = ir_generator.pop_value();
+            let element_type = ir_generator.resolve(element_type).to_ir();
+            let result = ir_generator.new_value();
+            ir_generator.emit(&format!(
+                "%{} = insertvalue {} {}, {} {}, {}",
+                result, t, tuple, element_type, value, i
+            ));
+            tuple = format!("%{}", result);
+        }
+        ir_generator.value = tuple;
+    }
+
+    fn evaluate(&self, evaluator: &mut Evaluator) -> Result<Value, Interrupt> {
+        let mut elements = Vec::new();
+        for element in &self.elements {
+            elements.push(element.evaluate(evaluator)?);
+        }
+        Ok(Value::Tuple(elements))
+    }
+}
@@ -1,24 +1,30 @@
 use crate::{
     error::{ExpectedToken, ParseError, TypeCheckError},
     ir_generator::IrGenerator,
-    lexer::{TokenKind, Type},
-    parser::Parser,
+    lexer::{Location, TokenKind, Type},
+    parser::{Parser, StatementKind},
+    stdlib,
     type_checker::TypeChecker,
 };
 
 #[derive(Debug, Clone)]
 pub struct Use {
-    function: String,
+    path: Vec<String>,
+    location: Location,
 }
 
 impl super::Instruction for Use {
     fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
         parser.bump();
-        let mut path = String::new();
+        let mut path = Vec::new();
+        let mut location = Location::default();
         while let Some(token) = parser.peek() {
             match token.kind {
-                TokenKind::Identifier(identifier) => path.push_str(&identifier),
-                TokenKind::PathSeparator => path.push('_'),
+                TokenKind::Identifier(identifier) => {
+                    path.push(identifier);
+                    location = token.location;
+                }
+                TokenKind::PathSeparator => (),
                 TokenKind::Semicolon => break,
                 _ => {
                     return Err(ParseError::UnexpectedToken {
@@ -29,14 +35,51 @@ impl super::Instruction for Use {
             }
             parser.bump();
         }
-        Ok(Self { function: path })
+        Ok(Self { path, location })
     }
 
-    fn check(&self, _type_checker: &mut TypeChecker) -> Result<Type, TypeCheckError> {
-        Ok(Type::Void)
+    // The function is imported with the other items of the block, so it can be called above the
+    // `use`
+    fn check(&mut self, _type_checker: &mut TypeChecker) -> Result<Type, TypeCheckError> {
+        Ok(Type::UNIT)
     }
 
     fn gen_ir(&self, ir_generator: &mut IrGenerator) {
-        ir_generator.stash = format!("declare void @_salt_stdlib_io_vga_print(i8*)");
+        let signature = ir_generator.env[&self.path.join("::")].clone();
+        ir_generator.declare(&signature);
+    }
+}
+
+impl Use {
+    // Makes the function known in the innermost block, see `TypeChecker::build_symbol_table`
+    pub fn import(&self, type_checker: &mut TypeChecker) -> Result<(), TypeCheckError> {
+        let Some((item, module)) = self.path.split_last() else {
+            return Err(TypeCheckError::UnresolvedImportNoToken);
+        };
+        let Some(module) = stdlib::module(&module.join("::")) else {
+            return Err(TypeCheckError::UnresolvedImportNoToken);
+        };
+        let StatementKind::Module { ast, .. } = module.kind else {
+            unreachable!()
+        };
+
+        let function = ast
+            .iter()
+            .filter_map(|statement| match &statement.kind {
+                StatementKind::ExternBlock(extern_block) => Some(&extern_block.functions),
+                _ => None,
+            })
+            .flatten()
+            .find(|function| function.name == *item);
+        match function {
+            Some(function) if function.public => type_checker.import_function(
+                item,
+                self.path.join("::"),
+                function.signature(),
+                &self.location,
+            ),
+            Some(_) => Err(TypeCheckError::PrivateItem(self.location.clone())),
+            None => Err(TypeCheckError::UnresolvedImport(self.location.clone())),
+        }
     }
 }
@@ -0,0 +1,210 @@
+use crate::{
+    error::{ParseError, TypeCheckError},
+    evaluator::{Evaluator, Interrupt, Value},
+    ir_generator::IrGenerator,
+    lexer::{Location, Type},
+    parser::Parser,
+    place::Place,
+    type_checker::{Access, TypeChecker},
+};
+
+#[derive(Debug, Clone)]
+pub struct Variable {
+    name: String,
+    // The key of the constant the name refers to, see `TypeChecker::constant_key`
+    key: String,
+    location: Location,
+    // The binding the name refers to, `None` for a constant or a struct without fields
+    pub local: Option<usize>,
+    // The name refers to a `const` or `static` item
+    constant: bool,
+    r#type: Type,
+}
+
+impl super::Instruction for Variable {
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        let name = parser.expect_ident()?;
+        Ok(Self {
+            key: name.clone(),
+            name,
+            location: parser.previous().location.clone(),
+            local: None,
+            constant: false,
+            r#type: Type::UNIT,
+        })
+    }
+
+    // Using a binding moves its value out of it unless the value is `Copy`. A `const` is a new
+    // value every time it is used, a `static` stays where it is
+    fn check(&mut self, type_checker: &mut TypeChecker) -> Result<Type, TypeCheckError> {
+        let Some((local, t)) = type_checker.variable(&self.name) else {
+            if let Some(constant) = type_checker.constant(&self.name) {
+                if constant.r#static && !constant.r#type.is_copy() {
+                    return Err(TypeCheckError::MoveOutOfStatic(self.location.clone()));
+                }
+                self.constant = true;
+                self.key = type_checker.constant_key(&self.name).unwrap().clone();
+                self.r#type = constant.r#type.clone();
+                return Ok(self.r#type.clone());
+            }
+            return match type_checker.struct_signature(&self.name) {
+                Some(signature) if signature.fields.is_empty() => {
+                    Ok(Type::Struct(signature.symbol.clone()))
+                }
+                _ => Err(TypeCheckError::UndeclaredVariableNoToken),
+            };
+        };
+        self.local = Some(local);
+        self.r#type = t.clone();
+        type_checker.move_variable(local, &self.location)?;
+        Ok(t)
+    }
+
+    fn gen_ir(&self, ir_generator: &mut IrGenerator) {
+        if self.constant && !ir_generator.constants[&self.key].r#static {
+            let value = ir_generator.evaluated[&self.key].clone();
+            ir_generator.value = ir_generator.constant(&value, &self.r#type);
+            return;
+        }
+        if !self.constant && self.local.is_none() {
+            ir_generator.value = "zeroinitializer".to_string();
+            return;
+        }
+        // A binding of a value that never exists has no stack slot
+        let t = ir_generator.resolve(&self.r#type);
+        if t == Type::Never {
+            ir_generator.value = "undef".to_string();
+            return;
+        }
+        let address = self.gen_address(ir_generator);
+        let result = ir_generator.new_value();
+        ir_generator.emit(&format!(
+            "%{} = load {}, {}* {}",
+            result,
+            t.to_ir(),
+            t.to_ir(),
+            address
+        ));
+        ir_generator.value = format!("%{}", result);
+    }
+
+    fn evaluate(&self, evaluator: &mut Evaluator) -> Result<Value, Interrupt> {
+        if self.constant {
+            return Ok(evaluator.constant(&self.key, &self.location)?);
+        }
+        match self.local {
+            Some(local) => Ok(evaluator.variable(local)),
+            None => Ok(Value::UNIT),
+        }
+    }
+}
+
+impl Variable {
+    // Finds the binding without using its value, a `const` and a struct without fields are
+    // values and not places
+    fn binding(&mut self, type_checker: &TypeChecker) -> Result<(usize, Type), TypeCheckError> {
+        let Some((local, t)) = type_checker.variable(&self.name) else {
+            return if type_checker.constant(&self.name).is_some()
+                || type_checker.struct_signature(&self.name).is_some()
+            {
+                Err(TypeCheckError::NotAPlace(self.location.clone()))
+            } else {
+                Err(TypeCheckError::UndeclaredVariableNoToken)
+            };
+        };
+        self.local = Some(local);
+        self.r#type = t.clone();
+        Ok((local, t))
+    }
+
+    // Whether the name refers to a `const` item rather than to a binding or a `static`
+    pub fn is_const(&self, type_checker: &TypeChecker) -> bool {
+        type_checker.variable(&self.name).is_none()
+            && type_checker
+                .constant(&self.name)
+                .is_some_and(|constant| !constant.r#static)
+    }
+
+    // A `static` is a place outside of every function, it can be read and borrowed for as long
+    // as the program runs but never changed
+    fn static_place(&mut self, type_checker: &TypeChecker) -> Option<Place> {
+        if type_checker.variable(&self.name).is_some() {
+            return None;
+        }
+        let constant = type_checker
+            .constant(&self.name)
+            .filter(|constant| constant.r#static)?;
+        self.constant = true;
+        self.key = type_checker.constant_key(&self.name).unwrap().clone();
+        self.r#type = constant.r#type.clone();
+        Some(Place {
+            r#type: self.r#type.clone(),
+            root: None,
+            through_deref: false,
+            mutable: false,
+            location: self.location.clone(),
+        })
+    }
+
+    // Checks the name without moving the value, for when only a part of it is used or it is
+    // borrowed
+    pub fn check_place(&mut self, type_checker: &mut TypeChecker) -> Result<Place, TypeCheckError> {
+        if let Some(place) = self.static_place(type_checker) {
+            return Ok(place);
+        }
+        let (local, t) = self.binding(type_checker)?;
+        type_checker.use_variable(local, &self.location)?;
+        Ok(Place {
+            r#type: t,
+            root: Some(local),
+            through_deref: false,
+            mutable: type_checker.is_mutable(local),
+            location: self.location.clone(),
+        })
+    }
+
+    // Checks the name as the target of an assignment, which does not need the old value. Returns
+    // whether the binding still holds one
+    pub fn check_assignee(
+        &mut self,
+        type_checker: &mut TypeChecker,
+    ) -> Result<(Place, bool), TypeCheckError> {
+        if let Some(place) = self.static_place(type_checker) {
+            return Ok((place, false));
+        }
+        let (local, t) = self.binding(type_checker)?;
+        let place = Place {
+            r#type: t,
+            root: Some(local),
+            through_deref: false,
+            mutable: type_checker.is_mutable(local),
+            location: self.location.clone(),
+        };
+        Ok((place, !type_checker.is_moved(local)))
+    }
+
+    // A `&mut` binding given to a function is borrowed again for the call rather than moved, so
+    // it can still be used after it. Returns `None` for every other binding
+    pub fn check_reborrow(
+        &mut self,
+        type_checker: &mut TypeChecker,
+    ) -> Result<Option<Type>, TypeCheckError> {
+        let Some((local, t)) = type_checker.variable(&self.name) else {
+            return Ok(None);
+        };
+        let Type::Reference { mutable: true, .. } = type_checker.resolve(&t) else {
+            return Ok(None);
+        };
+        let place = self.check_place(type_checker)?;
+        type_checker.access(local, Access::BorrowMut, &self.location)?;
+        type_checker.new_loan(local, true, true, &self.location);
+        Ok(Some(place.r#type))
+    }
+
+    pub fn gen_address(&self, ir_generator: &mut IrGenerator) -> String {
+        if self.constant {
+            return format!("@{}", ir_generator.constants[&self.key].symbol);
+        }
+        ir_generator.variable(self.local.unwrap())
+    }
+}
@@ -1,44 +1,107 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
-use crate::instruction::{
-    function_declaration::FunctionDeclaration, string_literal::StringLiteral,
+use crate::{
+    evaluator::Value,
+    instruction::Instruction,
+    lexer::{Location, Type},
+    parser::Statement,
+    stdlib,
+    type_checker::{Cleanup, ConstantSignature, FunctionSignature, StructSignature, needs_drop},
 };
 
+// The module of the default panic handler
+const PANIC_MODULE: &str = "std::panic";
+
 pub struct IrGenerator {
     pub current_loop: Vec<usize>,
+    // The loops being generated, innermost last, for `break` to jump out of
+    pub loops: Vec<usize>,
+    pub labels: Vec<usize>,
     pub values: Vec<usize>,
+    // The stack slot of each binding of the functions being generated, by the index the type
+    // checker gave the binding, and the code that allocates the slots on entry to each function
+    pub variables: Vec<HashMap<usize, String>>,
+    pub allocas: Vec<String>,
+    // The body of each deferred block of the functions being generated, by the index the type
+    // checker gave it, to generate at every exit of its scope
+    pub defers: Vec<HashMap<usize, Statement>>,
 
-    pub strings: Vec<StringLiteral>,
+    pub source_filename: String,
+    pub doc: Vec<String>,
+    pub strings: Vec<Vec<u8>>,
     pub function_declarations: Vec<String>,
+    // The symbols of the functions declared so far, each may only be declared once
+    declared: HashSet<String>,
     pub ir: String,
     pub stash: String,
+    pub value: String,
 
-    pub env: HashMap<String, FunctionDeclaration>,
+    pub env: HashMap<String, FunctionSignature>,
+    pub structs: HashMap<String, StructSignature>,
+    pub constants: HashMap<String, ConstantSignature>,
+    // The value of each `const` and `static` item, worked out by the evaluator
+    pub evaluated: HashMap<String, Value>,
+    pub integers: Vec<Type>,
+    // Arithmetic wraps instead of checking for overflow
+    pub release: bool,
+    // The function with `#[panic_handler]`, the stdlib has the default one
+    pub panic_handler: Option<String>,
 }
 
 impl IrGenerator {
-    pub fn new(env: HashMap<String, FunctionDeclaration>) -> Self {
+    pub fn new(
+        env: HashMap<String, FunctionSignature>,
+        structs: HashMap<String, StructSignature>,
+        constants: HashMap<String, ConstantSignature>,
+        evaluated: HashMap<String, Value>,
+        integers: Vec<Type>,
+    ) -> Self {
         Self {
             current_loop: Vec::new(),
+            loops: Vec::new(),
+            labels: Vec::new(),
             values: Vec::new(),
+            variables: Vec::new(),
+            allocas: Vec::new(),
+            defers: Vec::new(),
 
+            source_filename: String::new(),
+            doc: Vec::new(),
             strings: Vec::new(),
             function_declarations: Vec::new(),
+            declared: HashSet::new(),
             ir: String::new(),
             stash: String::new(),
+            value: String::new(),
 
             env,
+            structs,
+            constants,
+            evaluated,
+            integers,
+            release: false,
+            panic_handler: None,
         }
     }
 
     pub fn new_function(&mut self) {
         self.current_loop.push(0);
+        self.labels.push(0);
         self.values.push(0);
+        self.variables.push(HashMap::new());
+        self.allocas.push(String::new());
+        self.defers.push(HashMap::new());
     }
 
-    pub fn finish_function(&mut self) {
+    // Returns the code that allocates the stack slots of the function, it goes first in its
+    // entry block
+    pub fn finish_function(&mut self) -> String {
         self.current_loop.pop();
+        self.labels.pop();
         self.values.pop();
+        self.variables.pop();
+        self.defers.pop();
+        self.allocas.pop().unwrap()
     }
 
     pub fn new_loop(&mut self) -> usize {
@@ -46,24 +109,301 @@ impl IrGenerator {
         self.current_loop.last().unwrap() - 1
     }
 
+    pub fn new_label(&mut self) -> usize {
+        *self.labels.last_mut().unwrap() += 1;
+        self.labels.last().unwrap() - 1
+    }
+
+    // Ends the block after something that never finishes, the code after it goes in a new block
+    // that nothing jumps to, where it can use `undef` for the value of what came before
+    pub fn unreachable(&mut self, terminator: &str) {
+        let label = self.new_label();
+        self.emit(terminator);
+        self.emit(&format!("dead{}:", label));
+        self.value = "undef".to_string();
+    }
+
     pub fn new_value(&mut self) -> usize {
         *self.values.last_mut().unwrap() += 1;
         self.values.last().unwrap() - 1
     }
 
+    // The address of the stack slot of a binding
+    pub fn variable(&self, local: usize) -> String {
+        self.variables.last().unwrap()[&local].clone()
+    }
+
+    // Gives the binding a stack slot and returns its address. A binding in a deferred block is
+    // generated again at every exit of the scope, but it keeps the slot it got the first time
+    pub fn new_variable(&mut self, local: usize, t: &Type) -> String {
+        if let Some(address) = self.variables.last().unwrap().get(&local) {
+            return address.clone();
+        }
+        let address = format!("%local.{}", local);
+        let alloca = format!("  {} = alloca {}\n", address, self.resolve(t).to_ir());
+        self.allocas.last_mut().unwrap().push_str(&alloca);
+        self.variables
+            .last_mut()
+            .unwrap()
+            .insert(local, address.clone());
+        address
+    }
+
+    // Runs what has to happen when a scope is left
+    pub fn clean_up(&mut self, cleanup: &[Cleanup]) {
+        for cleanup in cleanup {
+            match cleanup {
+                Cleanup::Drop(local, t) => self.drop_variable(*local, t),
+                Cleanup::Defer(defer) => {
+                    let body = self.defers.last().unwrap()[defer].clone();
+                    body.gen_ir(self);
+                    self.pop_value();
+                }
+            }
+        }
+    }
+
+    // Drops the value a binding holds when it goes out of scope
+    pub fn drop_variable(&mut self, local: usize, t: &Type) {
+        let t = self.resolve(t);
+        let value = self.new_value();
+        let address = self.variable(local);
+        self.emit(&format!(
+            "%{} = load {}, {}* {}",
+            value,
+            t.to_ir(),
+            t.to_ir(),
+            address
+        ));
+        self.drop_value(&format!("%{}", value), &t);
+    }
+
+    // Runs the destructors of a value that goes out of scope, a struct is dropped before its fields
+    pub fn drop_value(&mut self, value: &str, t: &Type) {
+        let t = self.resolve(t);
+        let (elements, drop) = match &t {
+            Type::Tuple(elements) => (elements.clone(), None),
+            Type::Struct(symbol) => {
+                let signature = &self.structs[&symbol.to_string()];
+                (signature.fields.clone(), signature.drop.clone())
+            }
+            _ => return,
+        };
+        if let Some(drop) = drop {
+            self.emit(&format!("call void @{}({} {})", drop, t.to_ir(), value));
+        }
+        for (i, element) in elements.iter().enumerate() {
+            if !needs_drop(&self.structs, element) {
+                continue;
+            }
+            let field = self.new_value();
+            self.emit(&format!(
+                "%{} = extractvalue {} {}, {}",
+                field,
+                t.to_ir(),
+                value,
+                i
+            ));
+            self.drop_value(&format!("%{}", field), element);
+        }
+    }
+
+    // The type the type checker decided on for an integer variable
+    pub fn resolve(&self, t: &Type) -> Type {
+        resolve_integers(&self.integers, t)
+    }
+
+    // Writes out a value the evaluator worked out, as an operand or the initializer of a global
+    pub fn constant(&mut self, value: &Value, t: &Type) -> String {
+        let t = self.resolve(t);
+        match (value, &t) {
+            (Value::Int(bits), _) => bits.to_string(),
+            (Value::Bytes(bytes), _) => self.new_slice(bytes),
+            (Value::Tuple(elements), _) if elements.is_empty() => "zeroinitializer".to_string(),
+            (Value::Tuple(elements), Type::Tuple(types)) => {
+                self.constant_aggregate(elements, types)
+            }
+            (Value::Tuple(elements), Type::Struct(symbol)) => {
+                let fields = self.structs[&symbol.to_string()].fields.clone();
+                self.constant_aggregate(elements, &fields)
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn constant_aggregate(&mut self, elements: &[Value], types: &[Type]) -> String {
+        let elements = elements
+            .iter()
+            .zip(types)
+            .map(|(element, t)| {
+                format!("{} {}", self.resolve(t).to_ir(), self.constant(element, t))
+            })
+            .collect::<Vec<_>>();
+        format!("{{ {} }}", elements.join(", "))
+    }
+
+    // Appends a line of code to the stash, values must be numbered in the order they are emitted
+    pub fn emit(&mut self, line: &str) {
+        self.stash.push_str(line);
+        self.stash.push('\n');
+    }
+
+    // Declares a function that is defined outside the module, the first time it is needed
+    pub fn declare(&mut self, signature: &FunctionSignature) {
+        self.declare_symbol(&signature.symbol, &signature.declare_ir());
+    }
+
+    // Adds the declaration of a symbol unless it already is declared, for LLVM intrinsics
+    pub fn declare_symbol(&mut self, symbol: &str, declaration: &str) {
+        if self.declared.insert(symbol.to_string()) {
+            self.function_declarations.push(declaration.to_string());
+        }
+    }
+
+    // Calls the panic handler with a `str` and where the panic happened, the caller ends the block
+    // since the handler does not return
+    pub fn call_panic_handler(&mut self, message: &str, location: &Location) {
+        let handler = match &self.panic_handler {
+            Some(key) => self.env[key].clone(),
+            None => {
+                let handler = self
+                    .env
+                    .entry(format!("{PANIC_MODULE}::handler"))
+                    .or_insert_with(|| stdlib::function(PANIC_MODULE, "handler"))
+                    .clone();
+                self.declare(&handler);
+                handler
+            }
+        };
+        let file = self.new_slice(self.source_filename.clone().as_bytes());
+        let message = self.slice_argument(message, &Type::Str, handler.c_abi);
+        let file = self.slice_argument(&file, &Type::Str, handler.c_abi);
+        self.emit(&format!(
+            "call void @{}({}, {}, i32 {}, i32 {})",
+            handler.symbol, message, file, location.line, location.col
+        ));
+    }
+
+    // Panics with the message if the `i1` is true, the code after it goes in a new block that only
+    // the other case jumps to
+    pub fn panic_if(&mut self, condition: &str, message: &str, location: &Location) {
+        let label = self.new_label();
+        self.emit(&format!(
+            "br i1 {}, label %panic{}, label %continue{}",
+            condition, label, label
+        ));
+        self.emit(&format!("panic{}:", label));
+        let message = self.new_slice(message.as_bytes());
+        self.call_panic_handler(&message, location);
+        self.emit("unreachable");
+        self.emit(&format!("continue{}:", label));
+    }
+
+    // A slice argument of a call, the C ABI has no slices so the Rust stdlib takes
+    // `(ptr: *const u8, len: usize)`
+    pub fn slice_argument(&mut self, value: &str, t: &Type, c_abi: bool) -> String {
+        if !c_abi {
+            return format!("{} {}", t.to_ir(), value);
+        }
+        let pointer = self.new_value();
+        self.emit(&format!(
+            "%{} = extractvalue {} {}, 0",
+            pointer,
+            t.to_ir(),
+            value
+        ));
+        let length = self.new_value();
+        self.emit(&format!(
+            "%{} = extractvalue {} {}, 1",
+            length,
+            t.to_ir(),
+            value
+        ));
+        format!("i8* %{}, i64 %{}", pointer, length)
+    }
+
+    // Adds a constant and returns a slice of it
+    pub fn new_slice(&mut self, bytes: &[u8]) -> String {
+        format!("{{ i8* {}, i64 {} }}", self.new_string(bytes), bytes.len())
+    }
+
+    // Adds a NUL terminated constant and returns a pointer to its first byte
+    pub fn new_string(&mut self, bytes: &[u8]) -> String {
+        let str_num = self.strings.len();
+        self.strings.push(bytes.to_vec());
+        format!(
+            "getelementptr ([{} x i8], [{} x i8]* @.str.{}, i64 0, i64 0)",
+            bytes.len() + 1,
+            bytes.len() + 1,
+            str_num
+        )
+    }
+
     pub fn pop_stash(&mut self) -> String {
         std::mem::take(&mut self.stash)
     }
 
+    pub fn pop_value(&mut self) -> String {
+        std::mem::take(&mut self.value)
+    }
+
     pub fn get_ir(&mut self) -> String {
-        self.strings
-            .iter()
-            .enumerate()
-            .map(|(i, e)| e.to_ir(i) + "\n")
-            .collect::<String>()
+        format!("source_filename = \"{}\"\n", self.source_filename)
+            + &self
+                .doc
+                .iter()
+                .map(|line| format!(";{line}\n"))
+                .collect::<String>()
+            + "\n"
+            + &self
+                .strings
+                .iter()
+                .enumerate()
+                .map(|(i, e)| {
+                    format!(
+                        "@.str.{} = internal constant [{} x i8] c\"{}\\00\"\n",
+                        i,
+                        e.len() + 1,
+                        escape_ir(e)
+                    )
+                })
+                .collect::<String>()
             + "\n"
             + &self.function_declarations.join("\n")
             + &self.ir
             + &self.stash
     }
 }
+
+// The types the type checker decided on for the integer variables in a type
+pub fn resolve_integers(integers: &[Type], t: &Type) -> Type {
+    match t {
+        Type::IntVar(integer) => integers[*integer].clone(),
+        Type::Tuple(elements) => Type::Tuple(
+            elements
+                .iter()
+                .map(|element| resolve_integers(integers, element))
+                .collect(),
+        ),
+        Type::Pointer { mutable, pointee } => Type::Pointer {
+            mutable: *mutable,
+            pointee: Box::new(resolve_integers(integers, pointee)),
+        },
+        Type::Reference { mutable, referent } => Type::Reference {
+            mutable: *mutable,
+            referent: Box::new(resolve_integers(integers, referent)),
+        },
+        t => t.clone(),
+    }
+}
+
+// LLVM only understands `\XX` escapes, so everything but printable ASCII is written as one
+fn escape_ir(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|&byte| match byte {
+            0x20..=0x7E if byte != b'"' && byte != b'\\' => (byte as char).to_string(),
+            _ => format!("\\{byte:02X}"),
+        })
+        .collect()
+}
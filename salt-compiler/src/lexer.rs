@@ -2,34 +2,166 @@ use anyhow::Result;
 
 use logos::{Logos, Span};
 
-use crate::error::LexingError;
+use crate::{
+    error::{LexError, LexingError},
+    mangle::Symbol,
+};
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum Keyword {
     Use,
+    Pub,
 
     Fn,
     Raw,
+    Extern,
+    Struct,
+    Impl,
+    For,
 
     Loop,
+    Break,
+    Return,
+    Defer,
+
+    Let,
+    As,
+    Mut,
+    Const,
+    Static,
+    Macro,
 }
 
 impl std::fmt::Display for Keyword {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Keyword::Use => write!(f, "use"),
+            Keyword::Pub => write!(f, "pub"),
             Keyword::Fn => write!(f, "fn"),
             Keyword::Raw => write!(f, "raw"),
+            Keyword::Extern => write!(f, "extern"),
+            Keyword::Struct => write!(f, "struct"),
+            Keyword::Impl => write!(f, "impl"),
+            Keyword::For => write!(f, "for"),
             Keyword::Loop => write!(f, "loop"),
+            Keyword::Break => write!(f, "break"),
+            Keyword::Return => write!(f, "return"),
+            Keyword::Defer => write!(f, "defer"),
+            Keyword::Let => write!(f, "let"),
+            Keyword::As => write!(f, "as"),
+            Keyword::Mut => write!(f, "mut"),
+            Keyword::Const => write!(f, "const"),
+            Keyword::Static => write!(f, "static"),
+            Keyword::Macro => write!(f, "macro"),
         }
     }
 }
 
 #[derive(Debug, PartialEq, Clone, Copy)]
+pub enum IntType {
+    U8,
+    U16,
+    U32,
+    U64,
+    Usize,
+
+    I8,
+    I16,
+    I32,
+    I64,
+    Isize,
+}
+
+impl IntType {
+    pub fn name(self) -> &'static str {
+        match self {
+            IntType::U8 => "u8",
+            IntType::U16 => "u16",
+            IntType::U32 => "u32",
+            IntType::U64 => "u64",
+            IntType::Usize => "usize",
+            IntType::I8 => "i8",
+            IntType::I16 => "i16",
+            IntType::I32 => "i32",
+            IntType::I64 => "i64",
+            IntType::Isize => "isize",
+        }
+    }
+
+    // Salt only targets x86_64, so pointers are 64 bits wide
+    pub fn bits(self) -> u32 {
+        match self {
+            IntType::U8 | IntType::I8 => 8,
+            IntType::U16 | IntType::I16 => 16,
+            IntType::U32 | IntType::I32 => 32,
+            IntType::U64 | IntType::I64 | IntType::Usize | IntType::Isize => 64,
+        }
+    }
+
+    pub fn signed(self) -> bool {
+        matches!(
+            self,
+            IntType::I8 | IntType::I16 | IntType::I32 | IntType::I64 | IntType::Isize
+        )
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "u8" => IntType::U8,
+            "u16" => IntType::U16,
+            "u32" => IntType::U32,
+            "u64" => IntType::U64,
+            "usize" => IntType::Usize,
+            "i8" => IntType::I8,
+            "i16" => IntType::I16,
+            "i32" => IntType::I32,
+            "i64" => IntType::I64,
+            "isize" => IntType::Isize,
+            _ => return None,
+        })
+    }
+
+    // The smallest value a literal of this type can hold, a negative one only fits a signed type
+    pub fn min(self) -> i128 {
+        if self.signed() {
+            -(1 << (self.bits() - 1))
+        } else {
+            0
+        }
+    }
+
+    // The largest value a literal of this type can hold
+    pub fn max(self) -> u128 {
+        if self.signed() {
+            (1 << (self.bits() - 1)) - 1
+        } else {
+            (1 << self.bits()) - 1
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
 pub enum Type {
-    Void,
+    // The type of expressions that never finish, it fits wherever any other type is expected
+    Never,
 
     Str,
+    ByteStr,
+
+    Char,
+    Bool,
+    Int(IntType),
+
+    Pointer { mutable: bool, pointee: Box<Type> },
+    // A pointer the borrow checker vouches for, `&mut` is the only way to reach what it points to
+    Reference { mutable: bool, referent: Box<Type> },
+    // `()` is the unit type, the type of everything without a value
+    Tuple(Vec<Type>),
+    // A struct declared in Salt, found by its name
+    Struct(Symbol),
+
+    // An integer whose type is not known yet, only the type checker creates these
+    IntVar(usize),
 
     Any,
 }
@@ -37,29 +169,118 @@ pub enum Type {
 impl std::fmt::Display for Type {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Type::Void => write!(f, "`void`"),
-            Type::Str => write!(f, "`str`"),
             Type::Any => write!(f, "`T`"),
+            t => write!(f, "`{}`", t.name()),
         }
     }
 }
 
 impl Type {
-    pub fn to_ir(&self) -> &'static str {
+    pub const UNIT: Type = Type::Tuple(Vec::new());
+
+    pub fn name(&self) -> String {
+        match self {
+            Type::Never => "!".to_string(),
+            Type::Str => "str".to_string(),
+            Type::ByteStr => "[u8]".to_string(),
+            Type::Char => "char".to_string(),
+            Type::Bool => "bool".to_string(),
+            Type::Int(int) => int.name().to_string(),
+            Type::Pointer { mutable, pointee } => format!(
+                "*{} {}",
+                if *mutable { "mut" } else { "const" },
+                pointee.name()
+            ),
+            Type::Reference { mutable, referent } => {
+                format!("&{}{}", if *mutable { "mut " } else { "" }, referent.name())
+            }
+            Type::Tuple(elements) if elements.len() == 1 => format!("({},)", elements[0].name()),
+            Type::Tuple(elements) => format!(
+                "({})",
+                elements
+                    .iter()
+                    .map(Type::name)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Type::Struct(symbol) => symbol.name.clone(),
+            Type::IntVar(_) => "{integer}".to_string(),
+            Type::Any => "T".to_string(),
+        }
+    }
+
+    pub fn to_ir(&self) -> String {
+        match self {
+            Type::Never => "void".to_string(),
+            // Slices are the address of their first byte and their length in bytes
+            Type::Str | Type::ByteStr => "{ i8*, i64 }".to_string(),
+            Type::Char => "i32".to_string(),
+            Type::Bool => "i1".to_string(),
+            Type::Int(int) => format!("i{}", int.bits()),
+            // A pointer to nothing in particular is a pointer to bytes like in C
+            Type::Pointer { pointee, .. } if **pointee == Type::UNIT => "i8*".to_string(),
+            Type::Pointer { pointee, .. } => format!("{}*", pointee.to_ir()),
+            Type::Reference { referent, .. } => format!("{}*", referent.to_ir()),
+            // Tuples are anonymous structs, `()` is the empty one
+            Type::Tuple(elements) => format!(
+                "{{{}}}",
+                elements
+                    .iter()
+                    .map(|element| format!(" {}", element.to_ir()))
+                    .collect::<Vec<_>>()
+                    .join(",")
+                    + if elements.is_empty() { "" } else { " " }
+            ),
+            // Structs are named so their fields are only written out once
+            Type::Struct(symbol) => format!("%{}", symbol.mangle()),
+            Type::IntVar(_) | Type::Any => unreachable!(),
+        }
+    }
+
+    // Using a value of a type that is not `Copy` moves it, structs are never `Copy` and there is
+    // only ever one `&mut` to a value
+    pub fn is_copy(&self) -> bool {
         match self {
-            Type::Void => "void",
-            Type::Str => "i8*",
-            Type::Any => unreachable!(),
+            Type::Tuple(elements) => elements.iter().all(Type::is_copy),
+            Type::Struct(_) => false,
+            Type::Reference { mutable, .. } => !mutable,
+            _ => true,
+        }
+    }
+
+    pub fn contains_reference(&self) -> bool {
+        match self {
+            Type::Reference { .. } => true,
+            Type::Tuple(elements) => elements.iter().any(Type::contains_reference),
+            _ => false,
+        }
+    }
+
+    // Functions without a value return `void`, like in C
+    pub fn return_ir(&self) -> String {
+        if *self == Type::UNIT {
+            "void".to_string()
+        } else {
+            self.to_ir()
         }
     }
 }
 
 #[derive(Debug, PartialEq, Clone, Default)]
 pub struct Location {
-    pub line: usize,
-    pub col: usize,
+    // Kept narrow so errors, which hold several locations, stay small
+    pub line: u32,
+    pub col: u32,
     pub length: usize,
     pub source: [Option<String>; 3],
+    // Set on the tokens a macro expanded to, which come from the body of the macro
+    pub expansion: Option<Box<Expansion>>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Expansion {
+    pub name: String,
+    pub invocation: Location,
 }
 
 impl std::fmt::Display for Location {
@@ -69,9 +290,40 @@ impl std::fmt::Display for Location {
             f,
             "{} | {}{}",
             " ".repeat(self.line.ilog10() as usize + 1),
-            " ".repeat(self.col - 1),
+            " ".repeat(self.col as usize - 1),
             "^".repeat(self.length),
         )?;
+        // A macro that invokes itself is shown once with the number of times it did
+        let mut expansion = self.expansion.as_deref();
+        while let Some(current) = expansion {
+            let mut times = 1;
+            expansion = current.invocation.expansion.as_deref();
+            while let Some(next) = expansion
+                && next.name == current.name
+                && next.invocation.line == current.invocation.line
+                && next.invocation.col == current.invocation.col
+            {
+                times += 1;
+                expansion = next.invocation.expansion.as_deref();
+            }
+            match times {
+                1 => writeln!(f, "note: in this expansion of `{}!`", current.name)?,
+                _ => writeln!(
+                    f,
+                    "note: in {} nested expansions of `{}!`",
+                    times, current.name
+                )?,
+            }
+            writeln!(f)?;
+            write!(
+                f,
+                "{}",
+                Location {
+                    expansion: None,
+                    ..current.invocation.clone()
+                }
+            )?;
+        }
         Ok(())
     }
 }
@@ -93,7 +345,7 @@ impl Location {
 
         let mut i = 0;
         let mut line_start = 0;
-        while split_source_with_len[i].0 <= span.start {
+        while i < split_source_with_len.len() && split_source_with_len[i].0 <= span.start {
             line_start = split_source_with_len[i].0;
             let (_, source_line) = &split_source_with_len[i];
             source[0] = source[1].clone();
@@ -104,21 +356,36 @@ impl Location {
 
         source[0] = source[1].clone();
         source[1] = source[2].clone();
-        source[2] = Some(split_source_with_len[i].1.to_string());
+        source[2] = split_source_with_len
+            .get(i)
+            .map(|(_, source_line)| source_line.to_string());
 
-        let line = i;
-        let col = span.start - line_start + 1;
+        let line = i as u32;
+        let col = (span.start - line_start + 1) as u32;
         Self {
             line,
             col,
             length,
             source,
+            expansion: None,
+        }
+    }
+
+    // Stretches the location up to the end of `end`, spans over several lines keep only the start
+    // and so do spans that go in or out of the body of a macro
+    pub fn to(&self, end: &Location) -> Location {
+        if end.line != self.line || end.expansion != self.expansion {
+            return self.clone();
+        }
+        Location {
+            length: end.col as usize + end.length - self.col as usize,
+            ..self.clone()
         }
     }
 
     pub fn value(&self) -> String {
-        let start = self.col - 1;
-        let end = self.col - 1 + self.length;
+        let start = self.col as usize - 1;
+        let end = start + self.length;
         self.source[1].as_ref().unwrap()[start..end].to_string()
     }
 }
@@ -127,36 +394,139 @@ impl Location {
 pub struct Token {
     pub kind: TokenKind,
     pub location: Location,
+    // The comments in front of the token
+    pub trivia: Vec<Trivia>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Trivia {
+    Line(String),
+    Block(String),
+    // `///`, documents the item after it
+    Doc(String),
+    // `//!`, documents the item it is in
+    InnerDoc(String),
 }
 
 #[derive(Logos, Debug, PartialEq, Clone)]
 #[logos(error(LexingError, LexingError::from_lexer))]
 #[logos(skip r"[ \t\n\f]+")] // Ignore this regex pattern between tokens
 pub enum TokenKind {
-    #[regex(r"(use|raw|fn|loop)", |lex| match lex.slice() {
+    // Comments are never handed to the parser, `lex` attaches them to the next token
+    #[regex(r"//[^\n]*", |lex| {
+        let comment = lex.slice();
+        if let Some(doc) = comment.strip_prefix("///").filter(|doc| !doc.starts_with('/')) {
+            Trivia::Doc(doc.to_string())
+        } else if let Some(doc) = comment.strip_prefix("//!") {
+            Trivia::InnerDoc(doc.to_string())
+        } else {
+            Trivia::Line(comment[2..].to_string())
+        }
+    })]
+    #[token("/*", block_comment)]
+    Trivia(Trivia),
+
+    #[regex(r"(use|pub|raw|fn|extern|struct|impl|for|loop|break|return|defer|let|as|mut|const|static|macro)", |lex| match lex.slice() {
         "use" => Keyword::Use,
+        "pub" => Keyword::Pub,
         "fn" => Keyword::Fn,
         "raw" => Keyword::Raw,
+        "extern" => Keyword::Extern,
+        "struct" => Keyword::Struct,
+        "impl" => Keyword::Impl,
+        "for" => Keyword::For,
 
         "loop" => Keyword::Loop,
+        "break" => Keyword::Break,
+        "return" => Keyword::Return,
+        "defer" => Keyword::Defer,
+
+        "let" => Keyword::Let,
+        "as" => Keyword::As,
+        "mut" => Keyword::Mut,
+        "const" => Keyword::Const,
+        "static" => Keyword::Static,
+
+        "macro" => Keyword::Macro,
         _ => unreachable!(),
     }, priority = 3)]
     Keyword(Keyword),
 
-    #[regex(r"(void|str)", |lex| match lex.slice() {
-        "void" => Type::Void,
+    #[regex(r"(str|cstr|char|bool|[ui](8|16|32|64|size))", |lex| match lex.slice() {
         "str" => Type::Str,
-        _ => unreachable!(),
+        // NUL terminated strings are plain pointers to their first byte
+        "cstr" => Type::Pointer {
+            mutable: false,
+            pointee: Box::new(Type::Int(IntType::U8)),
+        },
+        "char" => Type::Char,
+        "bool" => Type::Bool,
+        int => Type::Int(IntType::from_name(int).unwrap()),
     })]
     Type(Type),
 
-    #[regex(r#""(?:[^"]|\\")*""#, |lex| {
-        let string = lex.slice().to_string();
-        let len = string.len();
-        string[1..len - 1].to_string()
+    #[token("true", |_| true)]
+    #[token("false", |_| false)]
+    BoolLiteral(bool),
+
+    // An optional suffix gives the literal its type, `0xB8000usize`
+    #[regex(r"[0-9][0-9_]*([ui](8|16|32|64|size))?", |lex| integer_literal(lex, 10))]
+    #[regex(r"0x[0-9a-fA-F_]+([ui](8|16|32|64|size))?", |lex| integer_literal(lex, 16))]
+    #[regex(r"0o[0-7_]+([ui](8|16|32|64|size))?", |lex| integer_literal(lex, 8))]
+    #[regex(r"0b[01_]+([ui](8|16|32|64|size))?", |lex| integer_literal(lex, 2))]
+    IntegerLiteral((u128, Option<IntType>)),
+
+    #[regex(r#""(?:[^"\\]|\\.)*""#, |lex| {
+        let slice = lex.slice();
+        let string = unescape(&slice[1..slice.len() - 1], 1, false)?;
+        Ok::<_, LexingError>(String::from_utf8(string).unwrap())
     })]
+    #[regex(r#""(?:[^"\\]|\\.)*\\?"#, |_| Err(LexingError::UnterminatedString))]
     StringLiteral(String),
 
+    #[regex(r#"b"(?:[^"\\]|\\.)*""#, |lex| {
+        let slice = lex.slice();
+        unescape(&slice[2..slice.len() - 1], 2, true)
+    })]
+    #[regex(r#"b"(?:[^"\\]|\\.)*\\?"#, |_| Err(LexingError::UnterminatedString))]
+    ByteStringLiteral(Vec<u8>),
+
+    #[regex(r#"c"(?:[^"\\]|\\.)*""#, |lex| {
+        let slice = lex.slice();
+        let string = unescape(&slice[2..slice.len() - 1], 2, false)?;
+        if string.contains(&0) {
+            return Err(LexingError::NulInCString);
+        }
+        Ok(string)
+    })]
+    #[regex(r#"c"(?:[^"\\]|\\.)*\\?"#, |_| Err(LexingError::UnterminatedString))]
+    CStringLiteral(Vec<u8>),
+
+    #[regex(r"'(?:[^'\\\n]|\\.)*'", |lex| {
+        let slice = lex.slice();
+        let string = unescape(&slice[1..slice.len() - 1], 1, false)?;
+        let mut chars = String::from_utf8(string).unwrap().chars().collect::<Vec<_>>();
+        match chars.len() {
+            0 => Err(LexingError::EmptyCharacterLiteral),
+            1 => Ok(chars.pop().unwrap()),
+            _ => Err(LexingError::MultipleCharacterLiteral),
+        }
+    })]
+    #[regex(r"'(?:[^'\\\n]|\\.)*\\?", |_| Err(LexingError::UnterminatedCharacterLiteral))]
+    CharLiteral(char),
+
+    #[regex(r"b'(?:[^'\\\n]|\\.)*'", |lex| {
+        let slice = lex.slice();
+        let bytes = unescape(&slice[2..slice.len() - 1], 2, true)?;
+        match bytes.len() {
+            0 => Err(LexingError::EmptyCharacterLiteral),
+            1 => Ok(bytes[0]),
+            _ => Err(LexingError::MultipleCharacterLiteral),
+        }
+    })]
+    #[regex(r"b'(?:[^'\\\n]|\\.)*\\?", |_| Err(LexingError::UnterminatedCharacterLiteral))]
+    ByteLiteral(u8),
+
     #[regex(r"@[a-zA-Z_][a-zA-Z0-9_]*", |lex| lex.slice().to_string())]
     Intrinsic(String),
 
@@ -169,6 +539,30 @@ pub enum TokenKind {
     #[token("->")]
     Arrow,
 
+    #[token("=>")]
+    FatArrow,
+
+    #[token(":")]
+    Colon,
+
+    #[token(",")]
+    Comma,
+
+    #[token(".")]
+    Dot,
+
+    #[token("..")]
+    DotDot,
+
+    #[token("...")]
+    Ellipsis,
+
+    #[token("=")]
+    Equals,
+
+    #[token("#")]
+    Hash,
+
     #[token("(")]
     OpenParen,
 
@@ -181,17 +575,66 @@ pub enum TokenKind {
     #[token("}")]
     CloseBrace,
 
+    #[token("[")]
+    OpenBracket,
+
+    #[token("]")]
+    CloseBracket,
+
     #[token(";")]
     Semicolon,
+
+    #[token("*")]
+    Star,
+
+    #[token("+")]
+    Plus,
+
+    #[token("-")]
+    Minus,
+
+    #[token("/")]
+    Slash,
+
+    #[token("%")]
+    Percent,
+
+    #[token("&")]
+    Ampersand,
+
+    #[token("!")]
+    Bang,
+
+    #[token("$")]
+    Dollar,
 }
 
 impl std::fmt::Display for TokenKind {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
+            TokenKind::Trivia(_) => write!(f, "comment"),
             TokenKind::Keyword(keyword) => write!(f, "keyword `{keyword}`"),
             TokenKind::Type(t) => write!(f, "{t}"),
 
             TokenKind::StringLiteral(string) => write!(f, "{string:?}"),
+            TokenKind::ByteStringLiteral(bytes) => {
+                write!(f, "b\"{}\"", bytes.escape_ascii())
+            }
+            TokenKind::CStringLiteral(bytes) => {
+                write!(f, "c\"{}\"", bytes.escape_ascii())
+            }
+            TokenKind::CharLiteral(c) => write!(f, "{c:?}"),
+            TokenKind::ByteLiteral(byte) => {
+                write!(f, "b'{}'", std::ascii::escape_default(*byte))
+            }
+            TokenKind::BoolLiteral(value) => write!(f, "`{value}`"),
+            TokenKind::IntegerLiteral((value, suffix)) => {
+                write!(
+                    f,
+                    "`{value}{}`",
+                    suffix.map(IntType::name).unwrap_or_default()
+                )
+            }
 
             TokenKind::Intrinsic(intrinsic) => write!(f, "intrinsic `{intrinsic}`"),
             TokenKind::Identifier(identifier) => {
@@ -203,25 +646,181 @@ impl std::fmt::Display for TokenKind {
             }
             TokenKind::PathSeparator => write!(f, "`::`"),
             TokenKind::Arrow => write!(f, "`->`"),
+            TokenKind::FatArrow => write!(f, "`=>`"),
+            TokenKind::Colon => write!(f, "`:`"),
+            TokenKind::Comma => write!(f, "`,`"),
+            TokenKind::Dot => write!(f, "`.`"),
+            TokenKind::DotDot => write!(f, "`..`"),
+            TokenKind::Ellipsis => write!(f, "`...`"),
+            TokenKind::Equals => write!(f, "`=`"),
+            TokenKind::Hash => write!(f, "`#`"),
             TokenKind::OpenParen => write!(f, "`(`"),
             TokenKind::CloseParen => write!(f, "`)`"),
             TokenKind::OpenBrace => write!(f, "`{{`"),
             TokenKind::CloseBrace => write!(f, "`}}`"),
+            TokenKind::OpenBracket => write!(f, "`[`"),
+            TokenKind::CloseBracket => write!(f, "`]`"),
             TokenKind::Semicolon => write!(f, "`;`"),
+            TokenKind::Star => write!(f, "`*`"),
+            TokenKind::Plus => write!(f, "`+`"),
+            TokenKind::Minus => write!(f, "`-`"),
+            TokenKind::Slash => write!(f, "`/`"),
+            TokenKind::Percent => write!(f, "`%`"),
+            TokenKind::Ampersand => write!(f, "`&`"),
+            TokenKind::Bang => write!(f, "`!`"),
+            TokenKind::Dollar => write!(f, "`$`"),
         }
     }
 }
 
+// Splits off the type suffix and parses the digits, the regex guarantees there is at least one
+fn integer_literal(
+    lex: &mut logos::Lexer<'_, TokenKind>,
+    radix: u32,
+) -> Result<(u128, Option<IntType>), LexingError> {
+    let slice = lex.slice();
+    let digits = if radix == 10 { slice } else { &slice[2..] };
+    let (digits, suffix) = match digits.find(['u', 'i']) {
+        Some(i) => (&digits[..i], IntType::from_name(&digits[i..])),
+        None => (digits, None),
+    };
+    let digits = digits.replace('_', "");
+    if digits.is_empty() {
+        return Err(LexingError::MissingDigits);
+    }
+    let value = u128::from_str_radix(&digits, radix).map_err(|_| LexingError::IntegerTooLarge)?;
+    Ok((value, suffix))
+}
+
+// Block comments nest, so the end has to be found by hand
+fn block_comment(lex: &mut logos::Lexer<'_, TokenKind>) -> Result<Trivia, LexingError> {
+    let mut depth = 1;
+    let mut i = 0;
+    let remainder = lex.remainder().as_bytes();
+    while depth > 0 {
+        match remainder.get(i..i + 2) {
+            Some(b"/*") => {
+                depth += 1;
+                i += 2;
+            }
+            Some(b"*/") => {
+                depth -= 1;
+                i += 2;
+            }
+            Some(_) => i += 1,
+            None => {
+                lex.bump(remainder.len());
+                return Err(LexingError::UnterminatedBlockComment);
+            }
+        }
+    }
+    lex.bump(i);
+    let comment = lex.slice();
+    Ok(Trivia::Block(comment[2..comment.len() - 2].to_string()))
+}
+
+// Decodes the escape sequences in the body of a literal, `offset` is where the body starts in the token
+// Byte literals may only contain ASCII, but their hex escapes can be any byte
+fn unescape(body: &str, offset: usize, bytes: bool) -> Result<Vec<u8>, LexingError> {
+    let mut string = Vec::new();
+    let mut chars = body.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        if c != '\\' {
+            if bytes && !c.is_ascii() {
+                return Err(LexingError::NonAsciiByte {
+                    character: c,
+                    span: offset + start..offset + start + c.len_utf8(),
+                });
+            }
+            string.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+            continue;
+        }
+
+        // The regex guarantees that every backslash is followed by a character
+        let (_, escape) = chars.next().unwrap();
+        let mut end = start + 1 + escape.len_utf8();
+        match escape {
+            'n' => string.push(b'\n'),
+            't' => string.push(b'\t'),
+            'r' => string.push(b'\r'),
+            '0' => string.push(b'\0'),
+            '\\' => string.push(b'\\'),
+            '"' => string.push(b'"'),
+            '\'' => string.push(b'\''),
+            'x' => {
+                let digits = body[end..]
+                    .get(..2)
+                    .filter(|digits| digits.chars().all(|c| c.is_ascii_hexdigit()));
+                if let Some(digits) = digits {
+                    end += digits.len();
+                }
+                let span = offset + start..offset + end;
+                let escape = body[start..end].to_string();
+                match digits.map(|digits| u8::from_str_radix(digits, 16).unwrap()) {
+                    Some(byte) if bytes || byte <= 0x7F => string.push(byte),
+                    Some(_) => return Err(LexingError::OutOfRangeHexEscape { escape, span }),
+                    None => return Err(LexingError::InvalidHexEscape { escape, span }),
+                }
+            }
+            'u' => {
+                let digits = body[end..]
+                    .strip_prefix('{')
+                    .and_then(|rest| rest.split_once('}'))
+                    .map(|(digits, _)| digits);
+                if let Some(digits) = digits {
+                    end += digits.len() + 2;
+                }
+                let span = offset + start..offset + end;
+                let escape = body[start..end].to_string();
+                if bytes {
+                    return Err(LexingError::UnicodeEscapeInByteLiteral { escape, span });
+                }
+                match digits
+                    .filter(|digits| {
+                        (1..=6).contains(&digits.len())
+                            && digits.chars().all(|c| c.is_ascii_hexdigit())
+                    })
+                    .and_then(|digits| char::from_u32(u32::from_str_radix(digits, 16).unwrap()))
+                {
+                    Some(c) => string.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes()),
+                    None => return Err(LexingError::InvalidUnicodeEscape { escape, span }),
+                }
+            }
+            _ => {
+                return Err(LexingError::UnknownEscape {
+                    escape: body[start..end].to_string(),
+                    span: offset + start..offset + end,
+                });
+            }
+        }
+        while chars.next_if(|(i, _)| *i < end).is_some() {}
+    }
+    Ok(string)
+}
+
 pub fn lex(source: &str) -> Result<Vec<Token>> {
     let mut tokens = Vec::new();
+    let mut trivia = Vec::new();
     let mut lex = TokenKind::lexer(source);
     while let Some(kind) = lex.next() {
         match kind {
+            Ok(TokenKind::Trivia(comment)) => trivia.push(comment),
             Ok(kind) => tokens.push(Token {
                 kind,
                 location: Location::new(source, lex.span()),
+                trivia: std::mem::take(&mut trivia),
             }),
-            Err(e) => return Err(e.into()),
+            Err(error) => {
+                let span = match error.span() {
+                    Some(span) => lex.span().start + span.start..lex.span().start + span.end,
+                    None => lex.span(),
+                };
+                return Err(LexError {
+                    error,
+                    location: Location::new(source, span),
+                }
+                .into());
+            }
         }
     }
     Ok(tokens)
@@ -0,0 +1,624 @@
+// `macro name { (pattern) => { expansion } ... }` defines a macro that `name!(...)` expands to
+// the expansion of the first rule whose pattern matches the tokens of the invocation. The `let`
+// bindings in an expansion get names of their own, so they can not clash with the code around it
+
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    error::{ExpectedToken, ParseError},
+    lexer::{Expansion, Keyword, Location, Token, TokenKind},
+    parser::Parser,
+};
+
+// How deep macros can expand to invocations of macros
+pub const DEPTH: usize = 64;
+
+#[derive(Debug)]
+pub struct Macro {
+    pub name: String,
+    pub location: Location,
+    rules: Vec<Rule>,
+}
+
+#[derive(Debug)]
+struct Rule {
+    matchers: Vec<Matcher>,
+    transcribers: Vec<Transcriber>,
+}
+
+// What a fragment of a pattern matches
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Fragment {
+    Expr,
+    Ident,
+    Ty,
+    Literal,
+    Block,
+    // A single token, or a group of tokens in delimiters
+    Tt,
+}
+
+#[derive(Debug)]
+enum Matcher {
+    Token(TokenKind),
+    Fragment {
+        name: String,
+        fragment: Fragment,
+    },
+    Repeat {
+        matchers: Vec<Matcher>,
+        separator: Option<TokenKind>,
+    },
+}
+
+#[derive(Debug)]
+enum Transcriber {
+    Token(Token),
+    // A name the body declares with `let`, it is renamed in every expansion
+    Local(Token),
+    Variable {
+        name: String,
+        location: Location,
+    },
+    Repeat {
+        transcribers: Vec<Transcriber>,
+        separator: Option<Token>,
+        location: Location,
+    },
+}
+
+// The tokens a fragment captured, or what it captured each time its repetition matched
+#[derive(Debug, Clone)]
+enum Binding {
+    Fragment(Vec<Token>, Fragment),
+    Repeat(Vec<Binding>),
+}
+
+type Bindings = HashMap<String, Binding>;
+
+impl Macro {
+    pub fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        parser.expect(&TokenKind::Keyword(Keyword::Macro))?;
+        let name = parser.expect_ident()?;
+        let location = parser.previous().location.clone();
+        parser.expect(&TokenKind::OpenBrace)?;
+        let mut rules = Vec::new();
+        while let Some(token) = parser.peek()
+            && token.kind != TokenKind::CloseBrace
+        {
+            parser.expect(&TokenKind::OpenParen)?;
+            let matchers = parse_matchers(parser)?;
+            parser.expect(&TokenKind::CloseParen)?;
+            parser.expect(&TokenKind::FatArrow)?;
+            parser.expect(&TokenKind::OpenBrace)?;
+            let mut transcribers = parse_transcribers(parser)?;
+            parser.expect(&TokenKind::CloseBrace)?;
+            if let Some(TokenKind::Semicolon) = parser.peek().map(|token| token.kind) {
+                parser.bump();
+            }
+
+            let mut bound = HashSet::new();
+            bound_names(&matchers, &mut bound);
+            check_variables(&transcribers, &bound)?;
+            let mut locals = HashSet::new();
+            let_names(&transcribers, &mut locals);
+            mark_locals(&mut transcribers, &locals);
+            rules.push(Rule {
+                matchers,
+                transcribers,
+            });
+        }
+        parser.expect(&TokenKind::CloseBrace)?;
+
+        Ok(Self {
+            name,
+            location,
+            rules,
+        })
+    }
+
+    // The tokens the invocation expands to, `id` tells the expansions apart for hygiene
+    pub fn expand(
+        &self,
+        arguments: &[Token],
+        invocation: &Location,
+        id: usize,
+    ) -> Result<Vec<Token>, ParseError> {
+        for rule in &self.rules {
+            let mut pos = 0;
+            if let Some(bindings) = match_sequence(&rule.matchers, arguments, &mut pos, &[])
+                && pos == arguments.len()
+            {
+                let expansion = Expansion {
+                    name: self.name.clone(),
+                    invocation: invocation.clone(),
+                };
+                let mut tokens = Vec::new();
+                transcribe(&rule.transcribers, &bindings, &expansion, id, &mut tokens)?;
+                return Ok(tokens);
+            }
+        }
+        Err(ParseError::NoMatchingRule {
+            location: invocation.clone(),
+            definition: Box::new(self.location.clone()),
+        })
+    }
+}
+
+impl Fragment {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "expr" => Some(Fragment::Expr),
+            "ident" => Some(Fragment::Ident),
+            "ty" => Some(Fragment::Ty),
+            "literal" => Some(Fragment::Literal),
+            "block" => Some(Fragment::Block),
+            "tt" => Some(Fragment::Tt),
+            _ => None,
+        }
+    }
+
+    // Where the fragment that starts at `start` ends, `stop` are the tokens that can follow it
+    fn matches(self, tokens: &[Token], start: usize, stop: &[TokenKind]) -> Option<usize> {
+        let token = tokens.get(start)?;
+        match self {
+            Fragment::Ident => matches!(token.kind, TokenKind::Identifier(_)).then_some(start + 1),
+            Fragment::Literal => matches!(
+                token.kind,
+                TokenKind::StringLiteral(_)
+                    | TokenKind::ByteStringLiteral(_)
+                    | TokenKind::CStringLiteral(_)
+                    | TokenKind::CharLiteral(_)
+                    | TokenKind::ByteLiteral(_)
+                    | TokenKind::BoolLiteral(_)
+                    | TokenKind::IntegerLiteral(_)
+            )
+            .then_some(start + 1),
+            Fragment::Block => match token.kind {
+                TokenKind::OpenBrace => group_end(tokens, start),
+                _ => None,
+            },
+            Fragment::Tt if is_open(&token.kind) => group_end(tokens, start),
+            Fragment::Tt => (!is_close(&token.kind)).then_some(start + 1),
+            // Everything up to a token that can follow it or that can not be part of it, with
+            // delimiters balanced
+            Fragment::Expr | Fragment::Ty => {
+                let mut end = start;
+                while let Some(token) = tokens.get(end)
+                    && !stop.contains(&token.kind)
+                    && !matches!(
+                        token.kind,
+                        TokenKind::Comma | TokenKind::Semicolon | TokenKind::FatArrow
+                    )
+                    && !(self == Fragment::Ty && token.kind == TokenKind::Equals)
+                {
+                    if is_close(&token.kind) {
+                        return None;
+                    }
+                    end = match is_open(&token.kind) {
+                        true => group_end(tokens, end)?,
+                        false => end + 1,
+                    };
+                }
+                (end > start).then_some(end)
+            }
+        }
+    }
+}
+
+fn is_open(kind: &TokenKind) -> bool {
+    matches!(
+        kind,
+        TokenKind::OpenParen | TokenKind::OpenBrace | TokenKind::OpenBracket
+    )
+}
+
+fn is_close(kind: &TokenKind) -> bool {
+    matches!(
+        kind,
+        TokenKind::CloseParen | TokenKind::CloseBrace | TokenKind::CloseBracket
+    )
+}
+
+// The index after the delimiter that closes the one at `start`
+fn group_end(tokens: &[Token], start: usize) -> Option<usize> {
+    let mut depth = 0;
+    for (i, token) in tokens.iter().enumerate().skip(start) {
+        if is_open(&token.kind) {
+            depth += 1;
+        } else if is_close(&token.kind) {
+            depth -= 1;
+            if depth == 0 {
+                return Some(i + 1);
+            }
+        }
+    }
+    None
+}
+
+// Parses the pattern of a rule up to the delimiter that closes it
+fn parse_matchers(parser: &mut Parser) -> Result<Vec<Matcher>, ParseError> {
+    let mut matchers = Vec::new();
+    let mut depth = 0;
+    loop {
+        let token = parser.peek().ok_or(ParseError::UnexpectedEOF)?;
+        match token.kind {
+            kind if is_close(&kind) && depth == 0 => return Ok(matchers),
+            TokenKind::Dollar => {
+                parser.bump();
+                if let Some(TokenKind::OpenParen) = parser.peek().map(|token| token.kind) {
+                    parser.bump();
+                    let inner = parse_matchers(parser)?;
+                    parser.expect(&TokenKind::CloseParen)?;
+                    matchers.push(Matcher::Repeat {
+                        matchers: inner,
+                        separator: parse_repetition(parser)?.map(|token| token.kind),
+                    });
+                } else {
+                    let name = parser.expect_ident()?;
+                    parser.expect(&TokenKind::Colon)?;
+                    let fragment = match parser.bump() {
+                        Some(Token {
+                            kind: TokenKind::Identifier(fragment),
+                            ..
+                        }) if Fragment::from_name(&fragment).is_some() => {
+                            Fragment::from_name(&fragment).unwrap()
+                        }
+                        Some(token) => Err(ParseError::UnexpectedToken {
+                            actual: Box::new(token),
+                            expected: ExpectedToken::Fragment,
+                        })?,
+                        None => Err(ParseError::UnexpectedEOF)?,
+                    };
+                    matchers.push(Matcher::Fragment { name, fragment });
+                }
+            }
+            kind => {
+                if is_open(&kind) {
+                    depth += 1;
+                } else if is_close(&kind) {
+                    depth -= 1;
+                }
+                parser.bump();
+                matchers.push(Matcher::Token(kind));
+            }
+        }
+    }
+}
+
+// Parses the body of a rule up to the delimiter that closes it
+fn parse_transcribers(parser: &mut Parser) -> Result<Vec<Transcriber>, ParseError> {
+    let mut transcribers = Vec::new();
+    let mut depth = 0;
+    loop {
+        let token = parser.peek().ok_or(ParseError::UnexpectedEOF)?;
+        match token.kind {
+            ref kind if is_close(kind) && depth == 0 => return Ok(transcribers),
+            TokenKind::Dollar => {
+                parser.bump();
+                if let Some(TokenKind::OpenParen) = parser.peek().map(|token| token.kind) {
+                    parser.bump();
+                    let inner = parse_transcribers(parser)?;
+                    parser.expect(&TokenKind::CloseParen)?;
+                    transcribers.push(Transcriber::Repeat {
+                        transcribers: inner,
+                        separator: parse_repetition(parser)?,
+                        location: token.location,
+                    });
+                } else {
+                    let name = parser.expect_ident()?;
+                    transcribers.push(Transcriber::Variable {
+                        name,
+                        location: token.location.to(&parser.previous().location),
+                    });
+                }
+            }
+            ref kind => {
+                if is_open(kind) {
+                    depth += 1;
+                } else if is_close(kind) {
+                    depth -= 1;
+                }
+                parser.bump();
+                transcribers.push(Transcriber::Token(token));
+            }
+        }
+    }
+}
+
+// Parses the `*` after `$(...)` and the separator in front of it
+fn parse_repetition(parser: &mut Parser) -> Result<Option<Token>, ParseError> {
+    let token = parser.bump().ok_or(ParseError::UnexpectedEOF)?;
+    match token.kind {
+        TokenKind::Star => Ok(None),
+        ref kind if !is_open(kind) && !is_close(kind) && *kind != TokenKind::Dollar => {
+            parser.expect(&TokenKind::Star)?;
+            Ok(Some(token))
+        }
+        _ => Err(ParseError::UnexpectedToken {
+            actual: Box::new(token),
+            expected: ExpectedToken::Specific {
+                kind: TokenKind::Star,
+            },
+        }),
+    }
+}
+
+fn bound_names(matchers: &[Matcher], names: &mut HashSet<String>) {
+    for matcher in matchers {
+        match matcher {
+            Matcher::Token(_) => (),
+            Matcher::Fragment { name, .. } => {
+                names.insert(name.clone());
+            }
+            Matcher::Repeat { matchers, .. } => bound_names(matchers, names),
+        }
+    }
+}
+
+// Every `$name` in the body has to be captured by the pattern
+fn check_variables(
+    transcribers: &[Transcriber],
+    bound: &HashSet<String>,
+) -> Result<(), ParseError> {
+    for transcriber in transcribers {
+        match transcriber {
+            Transcriber::Variable { name, location } if !bound.contains(name) => {
+                return Err(ParseError::UnboundMacroVariable(location.clone()));
+            }
+            Transcriber::Repeat { transcribers, .. } => check_variables(transcribers, bound)?,
+            _ => (),
+        }
+    }
+    Ok(())
+}
+
+// The names the body declares with `let`, names that come from the invocation are not in it
+fn let_names(transcribers: &[Transcriber], names: &mut HashSet<String>) {
+    let mut i = 0;
+    while i < transcribers.len() {
+        match &transcribers[i] {
+            Transcriber::Token(Token {
+                kind: TokenKind::Keyword(Keyword::Let),
+                ..
+            }) => {
+                // Everything in the pattern up to the type or the value
+                i += 1;
+                while let Some(Transcriber::Token(token)) = transcribers.get(i)
+                    && !matches!(token.kind, TokenKind::Colon | TokenKind::Equals)
+                {
+                    if let TokenKind::Identifier(name) = &token.kind {
+                        names.insert(name.clone());
+                    }
+                    i += 1;
+                }
+            }
+            Transcriber::Repeat { transcribers, .. } => {
+                let_names(transcribers, names);
+                i += 1;
+            }
+            _ => i += 1,
+        }
+    }
+}
+
+// Marks the uses of the local names, but not fields, methods, functions or macros of that name
+fn mark_locals(transcribers: &mut [Transcriber], locals: &HashSet<String>) {
+    for i in 0..transcribers.len() {
+        let after_dot = i > 0
+            && matches!(
+                &transcribers[i - 1],
+                Transcriber::Token(Token {
+                    kind: TokenKind::Dot,
+                    ..
+                })
+            );
+        let called = matches!(
+            transcribers.get(i + 1),
+            Some(Transcriber::Token(Token {
+                kind: TokenKind::OpenParen | TokenKind::Bang,
+                ..
+            }))
+        );
+        let local = !after_dot
+            && !called
+            && matches!(
+                &transcribers[i],
+                Transcriber::Token(Token {
+                    kind: TokenKind::Identifier(name),
+                    ..
+                }) if locals.contains(name)
+            );
+        match &mut transcribers[i] {
+            Transcriber::Token(token) if local => {
+                transcribers[i] = Transcriber::Local(token.clone());
+            }
+            Transcriber::Repeat { transcribers, .. } => mark_locals(transcribers, locals),
+            _ => (),
+        }
+    }
+}
+
+// The tokens that can come first in what `matchers` matches, followed by `follow`
+fn first(matchers: &[Matcher], follow: &[TokenKind]) -> Vec<TokenKind> {
+    match matchers.first() {
+        None => follow.to_vec(),
+        Some(Matcher::Token(kind)) => vec![kind.clone()],
+        Some(Matcher::Fragment { .. }) => Vec::new(),
+        // A repetition can match nothing, so what comes after it can come first too
+        Some(Matcher::Repeat {
+            matchers: inner, ..
+        }) => {
+            let mut kinds = first(inner, &[]);
+            kinds.extend(first(&matchers[1..], follow));
+            kinds
+        }
+    }
+}
+
+// Matches the tokens from `pos` on, `follow` are the tokens that can come after the sequence
+fn match_sequence(
+    matchers: &[Matcher],
+    tokens: &[Token],
+    pos: &mut usize,
+    follow: &[TokenKind],
+) -> Option<Bindings> {
+    let mut bindings = Bindings::new();
+    for (i, matcher) in matchers.iter().enumerate() {
+        let stop = first(&matchers[i + 1..], follow);
+        match matcher {
+            Matcher::Token(kind) => {
+                if tokens.get(*pos)?.kind != *kind {
+                    return None;
+                }
+                *pos += 1;
+            }
+            Matcher::Fragment { name, fragment } => {
+                let end = fragment.matches(tokens, *pos, &stop)?;
+                bindings.insert(
+                    name.clone(),
+                    Binding::Fragment(tokens[*pos..end].to_vec(), *fragment),
+                );
+                *pos = end;
+            }
+            Matcher::Repeat {
+                matchers: inner,
+                separator,
+            } => {
+                let mut inner_follow = stop.clone();
+                inner_follow.extend(separator.clone());
+                let mut iterations = Vec::new();
+                loop {
+                    let mut next = *pos;
+                    if !iterations.is_empty()
+                        && let Some(separator) = separator
+                    {
+                        if tokens.get(next).map(|token| &token.kind) != Some(separator) {
+                            break;
+                        }
+                        next += 1;
+                    }
+                    match tokens.get(next) {
+                        Some(token) if !stop.contains(&token.kind) => (),
+                        _ => break,
+                    }
+                    let iteration = match_sequence(inner, tokens, &mut next, &inner_follow)?;
+                    // A repetition that matches nothing would match it forever
+                    if next == *pos {
+                        break;
+                    }
+                    iterations.push(iteration);
+                    *pos = next;
+                }
+
+                let mut names = HashSet::new();
+                bound_names(inner, &mut names);
+                for name in names {
+                    let repeated = iterations
+                        .iter_mut()
+                        .map(|iteration| iteration.remove(&name).unwrap())
+                        .collect();
+                    bindings.insert(name, Binding::Repeat(repeated));
+                }
+            }
+        }
+    }
+    Some(bindings)
+}
+
+fn used_names(transcribers: &[Transcriber], names: &mut Vec<String>) {
+    for transcriber in transcribers {
+        match transcriber {
+            Transcriber::Variable { name, .. } => names.push(name.clone()),
+            Transcriber::Repeat { transcribers, .. } => used_names(transcribers, names),
+            _ => (),
+        }
+    }
+}
+
+// Writes out the body with the captured tokens in place of the fragments
+fn transcribe(
+    transcribers: &[Transcriber],
+    bindings: &Bindings,
+    expansion: &Expansion,
+    id: usize,
+    tokens: &mut Vec<Token>,
+) -> Result<(), ParseError> {
+    // The tokens of the body are in the expansion, the captured ones stay where they were written
+    let expanded = |token: &Token| Token {
+        location: Location {
+            expansion: Some(Box::new(expansion.clone())),
+            ..token.location.clone()
+        },
+        ..token.clone()
+    };
+    for transcriber in transcribers {
+        match transcriber {
+            Transcriber::Token(token) => tokens.push(expanded(token)),
+            Transcriber::Local(token) => {
+                let TokenKind::Identifier(name) = &token.kind else {
+                    unreachable!()
+                };
+                tokens.push(Token {
+                    kind: TokenKind::Identifier(format!("{name}#{id}")),
+                    ..expanded(token)
+                });
+            }
+            Transcriber::Variable { name, location } => match &bindings[name] {
+                // An expression stays one expression wherever it ends up
+                Binding::Fragment(captured, Fragment::Expr) => {
+                    let delimiter = |kind, token: &Token| Token {
+                        kind,
+                        location: token.location.clone(),
+                        trivia: Vec::new(),
+                    };
+                    tokens.push(delimiter(TokenKind::OpenParen, &captured[0]));
+                    tokens.extend(captured.iter().cloned());
+                    tokens.push(delimiter(TokenKind::CloseParen, captured.last().unwrap()));
+                }
+                Binding::Fragment(captured, _) => tokens.extend(captured.iter().cloned()),
+                Binding::Repeat(_) => {
+                    return Err(ParseError::RepeatingMacroVariable(location.clone()));
+                }
+            },
+            Transcriber::Repeat {
+                transcribers,
+                separator,
+                location,
+            } => {
+                let mut names = Vec::new();
+                used_names(transcribers, &mut names);
+                let repeated = names
+                    .into_iter()
+                    .filter_map(|name| match &bindings[&name] {
+                        Binding::Repeat(iterations) => Some((name, iterations)),
+                        Binding::Fragment(..) => None,
+                    })
+                    .collect::<Vec<_>>();
+                let Some((_, iterations)) = repeated.first() else {
+                    return Err(ParseError::MacroRepetition(location.clone()));
+                };
+                let count = iterations.len();
+                if repeated
+                    .iter()
+                    .any(|(_, iterations)| iterations.len() != count)
+                {
+                    return Err(ParseError::MacroRepetition(location.clone()));
+                }
+                for i in 0..count {
+                    if i > 0
+                        && let Some(separator) = separator
+                    {
+                        tokens.push(expanded(separator));
+                    }
+                    let mut inner = bindings.clone();
+                    for (name, iterations) in &repeated {
+                        inner.insert(name.clone(), iterations[i].clone());
+                    }
+                    transcribe(transcribers, &inner, expansion, id, tokens)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
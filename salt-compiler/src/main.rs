@@ -2,34 +2,69 @@ use anyhow::Result;
 
 use std::{
     fs::File,
-    io::{Read, Write},
+    io::{Read, Write, stdin},
+    path::Path,
 };
 
 use crate::instruction::Instruction;
 
 mod compile;
 mod error;
+mod evaluator;
 mod instruction;
 mod ir_generator;
 mod lexer;
+mod macros;
+mod mangle;
 mod parser;
+mod place;
+mod stdlib;
 mod type_checker;
 
 fn main() -> Result<()> {
+    let arguments = std::env::args().skip(1).collect::<Vec<_>>();
+    if arguments
+        .first()
+        .is_some_and(|command| command == "demangle")
+    {
+        return demangle(&arguments[1..]);
+    }
+
     let mut source = Vec::new();
-    let module = "./salt_code/main.salt".to_string();
-    let _read = File::open(&module)
+    let path = "./salt_code/main.salt".to_string();
+    let _read = File::open(&path).unwrap().read_to_end(&mut source).unwrap();
+    let module = Path::new(&path)
+        .file_stem()
         .unwrap()
-        .read_to_end(&mut source)
-        .unwrap();
+        .to_string_lossy()
+        .to_string();
 
     let tokens = lexer::lex(&String::from_utf8_lossy(&source).replace("\t", "    "))?;
 
-    let ast = parser::Parser::new(tokens, module).parse()?;
-    let mut type_checker = type_checker::TypeChecker::new(&ast);
-    type_checker.build_symbol_table(&ast);
+    let mut ast = parser::Parser::new(tokens, module).parse()?;
+    let mut type_checker = type_checker::TypeChecker::new();
+    type_checker.build_symbol_table(&ast)?;
     ast.check(&mut type_checker)?;
-    let mut ir_generator = ir_generator::IrGenerator::new(type_checker.functions);
+    for warning in &type_checker.warnings {
+        eprintln!("{warning}");
+    }
+    let integers = type_checker.integer_types();
+    let release = arguments.iter().any(|argument| argument == "--release");
+    let mut evaluator =
+        evaluator::Evaluator::new(&ast, type_checker.structs.clone(), integers.clone());
+    evaluator.release = release;
+    let evaluated = evaluator.run()?;
+    let panic_handler = type_checker.panic_handler.take().map(|(key, _)| key);
+    let mut ir_generator = ir_generator::IrGenerator::new(
+        type_checker.functions,
+        type_checker.structs,
+        type_checker.constants,
+        evaluated,
+        integers,
+    );
+    ir_generator.source_filename = path;
+    ir_generator.release = release;
+    ir_generator.panic_handler = panic_handler;
     ast.gen_ir(&mut ir_generator);
     println!("{}", ir_generator.get_ir());
     File::create("kernel.ll")
@@ -43,6 +78,23 @@ fn main() -> Result<()> {
     Ok(())
 }
 
+// `saltc demangle [symbols]` demangles the given symbols, or every symbol read from stdin
+fn demangle(symbols: &[String]) -> Result<()> {
+    if symbols.is_empty() {
+        for line in stdin().lines() {
+            println!("{}", mangle::demangle_text(&line?));
+        }
+    } else {
+        for symbol in symbols {
+            match mangle::Symbol::demangle(symbol) {
+                Some(demangled) => println!("{demangled}"),
+                None => println!("{symbol}"),
+            }
+        }
+    }
+    Ok(())
+}
+
 #[allow(dead_code)]
 fn build_minimal_llvm_ir_kernel() {
     let ir = r#"
@@ -0,0 +1,187 @@
+// Functions defined in Salt are emitted under mangled symbols, so items of the same name in
+// different modules or blocks do not clash. `main::Lock::drop` is `_S4mainM4Lock4dropE`:
+//
+//   symbol = "_S" { ident } item [ "I" ident { ident } "E" ] "E"
+//   item   = ( ident | "M" ident ident ) [ "D" number "_" ]
+//   ident  = the byte length followed by the identifier
+//
+// The idents before the item are the module path and the functions a nested item is in, `D`
+// tells apart nested items of the same path, or the nested types of methods, and `M` is a method
+// on the type before its name
+
+const PREFIX: &str = "_S";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Symbol {
+    pub path: Vec<String>,
+    pub receiver: Option<String>,
+    pub name: String,
+    // Tells apart nested items of the same path, 0 for the first one
+    pub disambiguator: usize,
+    pub generics: Vec<String>,
+}
+
+impl std::fmt::Display for Symbol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for module in &self.path {
+            write!(f, "{module}::")?;
+        }
+        if let Some(receiver) = &self.receiver {
+            write!(f, "{receiver}::")?;
+        }
+        write!(f, "{}", self.name)?;
+        if self.disambiguator > 0 {
+            write!(f, "#{}", self.disambiguator)?;
+        }
+        if !self.generics.is_empty() {
+            write!(f, "<{}>", self.generics.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+impl Symbol {
+    pub fn new(path: &[String], name: &str) -> Self {
+        Self {
+            path: path.to_vec(),
+            receiver: None,
+            name: name.to_string(),
+            disambiguator: 0,
+            generics: Vec::new(),
+        }
+    }
+
+    pub fn mangle(&self) -> String {
+        let mut symbol = String::from(PREFIX);
+        for module in &self.path {
+            push_ident(&mut symbol, module);
+        }
+        if let Some(receiver) = &self.receiver {
+            symbol.push('M');
+            push_ident(&mut symbol, receiver);
+        }
+        push_ident(&mut symbol, &self.name);
+        if self.disambiguator > 0 {
+            symbol.push_str(&format!("D{}_", self.disambiguator));
+        }
+        if !self.generics.is_empty() {
+            symbol.push('I');
+            for generic in &self.generics {
+                push_ident(&mut symbol, generic);
+            }
+            symbol.push('E');
+        }
+        symbol.push('E');
+        symbol
+    }
+
+    pub fn demangle(symbol: &str) -> Option<Self> {
+        match Self::parse(symbol)? {
+            (demangled, len) if len == symbol.len() => Some(demangled),
+            _ => None,
+        }
+    }
+
+    // Parses a symbol from the start of `input`, returns it and how many bytes it spans
+    fn parse(input: &str) -> Option<(Self, usize)> {
+        let mut rest = input.strip_prefix(PREFIX)?;
+
+        let mut idents = Vec::new();
+        let mut receiver = None;
+        loop {
+            if let Some(method) = rest.strip_prefix('M') {
+                let (ident, method) = parse_ident(method)?;
+                receiver = Some(ident);
+                let (ident, method) = parse_ident(method)?;
+                idents.push(ident);
+                rest = method;
+                break;
+            }
+            match parse_ident(rest) {
+                Some((ident, tail)) => {
+                    idents.push(ident);
+                    rest = tail;
+                }
+                None => break,
+            }
+        }
+        let name = idents.pop()?;
+
+        let mut disambiguator = 0;
+        if let Some(tail) = rest.strip_prefix('D') {
+            let digits = tail.bytes().take_while(u8::is_ascii_digit).count();
+            if digits == 0 || tail.starts_with('0') {
+                return None;
+            }
+            disambiguator = tail[..digits].parse().ok()?;
+            rest = tail[digits..].strip_prefix('_')?;
+        }
+
+        let mut generics = Vec::new();
+        if let Some(mut tail) = rest.strip_prefix('I') {
+            while let Some((generic, next)) = parse_ident(tail) {
+                generics.push(generic);
+                tail = next;
+            }
+            if generics.is_empty() {
+                return None;
+            }
+            rest = tail.strip_prefix('E')?;
+        }
+        rest = rest.strip_prefix('E')?;
+
+        let symbol = Self {
+            path: idents,
+            receiver,
+            name,
+            disambiguator,
+            generics,
+        };
+        Some((symbol, input.len() - rest.len()))
+    }
+}
+
+fn push_ident(symbol: &mut String, ident: &str) {
+    symbol.push_str(&ident.len().to_string());
+    symbol.push_str(ident);
+}
+
+fn parse_ident(input: &str) -> Option<(String, &str)> {
+    let digits = input.bytes().take_while(u8::is_ascii_digit).count();
+    if digits == 0 || input.starts_with('0') {
+        return None;
+    }
+    let len = input[..digits].parse::<usize>().ok()?;
+    let ident = input.get(digits..digits + len)?;
+    if !ident.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        || !ident.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    {
+        return None;
+    }
+    Some((ident.to_string(), &input[digits + len..]))
+}
+
+// Replaces every mangled symbol in `text` with its demangled form, leaves everything else as is
+pub fn demangle_text(text: &str) -> String {
+    let mut demangled = String::new();
+    let mut i = 0;
+    while i < text.len() {
+        let at_boundary = !text[..i]
+            .chars()
+            .next_back()
+            .is_some_and(|c| c.is_ascii_alphanumeric() || c == '_');
+        if at_boundary
+            && text[i..].starts_with(PREFIX)
+            && let Some((symbol, len)) = Symbol::parse(&text[i..])
+            && !text[i + len..].starts_with(|c: char| c.is_ascii_alphanumeric() || c == '_')
+        {
+            demangled.push_str(&symbol.to_string());
+            i += len;
+            continue;
+        }
+        let c = text[i..].chars().next().unwrap();
+        demangled.push(c);
+        i += c.len_utf8();
+    }
+    demangled
+}
@@ -1,12 +1,52 @@
+use std::{collections::HashMap, rc::Rc};
+
 use crate::{
-    error::{ExpectedToken, ParseError, TypeCheckError},
+    error::{EvaluationError, ExpectedToken, ParseError, TypeCheckError},
+    evaluator::{Evaluator, Interrupt, Value},
     instruction::{
-        Instruction, block::Block, function_call::FunctionCall,
-        function_declaration::FunctionDeclaration, intrinsic::Intrinsic, r#loop::Loop,
-        string_literal::StringLiteral, r#use::Use,
+        Instruction,
+        assert::Assert,
+        assignment::Assignment,
+        attribute::Attribute,
+        binary::Binary,
+        block::Block,
+        bool_literal::BoolLiteral,
+        r#break::Break,
+        byte_literal::ByteLiteral,
+        byte_string_literal::ByteStringLiteral,
+        c_string_literal::CStringLiteral,
+        cast::Cast,
+        char_literal::CharLiteral,
+        const_declaration::ConstDeclaration,
+        defer::Defer,
+        deref::Deref,
+        extern_block::ExternBlock,
+        field::Field,
+        function_call::FunctionCall,
+        function_declaration::{FunctionDeclaration, Parameter},
+        r#impl::Impl,
+        integer_literal::IntegerLiteral,
+        intrinsic::Intrinsic,
+        r#let::Let,
+        r#loop::Loop,
+        method_call::MethodCall,
+        negate::Negate,
+        panic::Panic,
+        print::Print,
+        raw_block::RawBlock,
+        reference::Reference,
+        r#return::Return,
+        slice::Slice,
+        string_literal::StringLiteral,
+        struct_declaration::StructDeclaration,
+        tuple::Tuple,
+        r#use::Use,
+        variable::Variable,
     },
     ir_generator::IrGenerator,
-    lexer::{Keyword, Location, Token, TokenKind, Type},
+    lexer::{IntType, Keyword, Location, Token, TokenKind, Trivia, Type},
+    macros::{DEPTH, Macro},
+    mangle::Symbol,
     type_checker::TypeChecker,
 };
 
@@ -19,78 +59,91 @@ pub struct Statement {
 
 #[derive(Debug, Clone)]
 pub enum StatementKind {
-    Module { name: String, ast: Vec<Statement> },
+    Module {
+        name: String,
+        doc: Vec<String>,
+        ast: Vec<Statement>,
+    },
 
     FunctionDeclaration(FunctionDeclaration),
     FunctionCall(FunctionCall),
+    ConstDeclaration(ConstDeclaration),
+    ExternBlock(ExternBlock),
+    StructDeclaration(StructDeclaration),
+    Impl(Impl),
+
+    Variable(Variable),
+    Let(Let),
 
     Intrinsic(Intrinsic),
+    Print(Print),
+    Panic(Panic),
+    Assert(Assert),
 
     Use(Use),
 
     StringLiteral(StringLiteral),
+    ByteStringLiteral(ByteStringLiteral),
+    CStringLiteral(CStringLiteral),
+    CharLiteral(CharLiteral),
+    ByteLiteral(ByteLiteral),
+    BoolLiteral(BoolLiteral),
+    IntegerLiteral(IntegerLiteral),
+    Tuple(Tuple),
+
+    Cast(Cast),
+    Binary(Binary),
+    Negate(Negate),
+    Field(Field),
+    MethodCall(MethodCall),
+    Slice(Slice),
+    Reference(Reference),
+    Deref(Deref),
+    Assignment(Assignment),
 
     Loop(Loop),
+    Break(Break),
+    Return(Return),
+    Defer(Defer),
     Block(Block),
+    RawBlock(RawBlock),
 
     Empty,
 }
 
 impl Instruction for Statement {
     fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
-        let token = if let Some(token) = parser.peek() {
-            token
-        } else {
-            return Err(ParseError::UnexpectedEOF);
-        };
-        let location = token.location.clone();
-
-        let kind = match token.kind {
-            TokenKind::Keyword(Keyword::Use) => StatementKind::Use(Use::parse(parser)?),
-            TokenKind::Keyword(Keyword::Fn) | TokenKind::Keyword(Keyword::Raw) => {
-                StatementKind::FunctionDeclaration(FunctionDeclaration::parse(parser)?)
-            }
-            TokenKind::Keyword(Keyword::Loop) => StatementKind::Loop(Loop::parse(parser)?),
+        // A macro only exists for the parser, it leaves nothing behind in the AST
+        if let Some(Token {
+            kind: TokenKind::Keyword(Keyword::Macro),
+            location,
+            ..
+        }) = parser.peek()
+        {
+            parser.define_macro()?;
+            return Ok(Statement {
+                kind: StatementKind::Empty,
+                location,
+                returns: false,
+            });
+        }
 
-            TokenKind::StringLiteral(_) => {
-                StatementKind::StringLiteral(StringLiteral::parse(parser)?)
-            }
-
-            TokenKind::Intrinsic(_) => StatementKind::Intrinsic(Intrinsic::parse(parser)?),
-            TokenKind::Identifier(_) => StatementKind::FunctionCall(FunctionCall::parse(parser)?),
-            TokenKind::Type(_) => Err(ParseError::UnexpectedToken {
-                actual: Box::new(token),
-                expected: ExpectedToken::Statement,
-            })?,
-            TokenKind::PathSeparator => Err(ParseError::UnexpectedToken {
-                actual: Box::new(token),
-                expected: ExpectedToken::Statement,
-            })?,
-            TokenKind::Arrow => Err(ParseError::UnexpectedToken {
-                actual: Box::new(token),
-                expected: ExpectedToken::Statement,
-            })?,
-            TokenKind::OpenParen => Err(ParseError::UnexpectedToken {
-                actual: Box::new(token),
-                expected: ExpectedToken::Statement,
-            })?,
-            TokenKind::CloseParen => Err(ParseError::UnexpectedToken {
-                actual: Box::new(token),
-                expected: ExpectedToken::Statement,
-            })?,
-            TokenKind::OpenBrace => StatementKind::Block(Block::parse(parser)?),
-            TokenKind::CloseBrace => Err(ParseError::UnexpectedToken {
-                actual: Box::new(token),
-                expected: ExpectedToken::Statement,
-            })?,
-            TokenKind::Semicolon => StatementKind::Empty,
-        };
+        let Statement { kind, location, .. } = Statement::parse_expression(parser)?;
 
         let returns = match &kind {
-            StatementKind::FunctionDeclaration(function_declaration) => {
-                function_declaration.body.last().returns
+            StatementKind::FunctionDeclaration(_)
+            | StatementKind::ConstDeclaration(_)
+            | StatementKind::ExternBlock(_)
+            | StatementKind::StructDeclaration(_)
+            | StatementKind::Impl(_) => false,
+            StatementKind::Loop(r#loop) => {
+                parser.end_statement(true)?;
+                r#loop.body.last().returns
+            }
+            StatementKind::Defer(_) => {
+                parser.end_statement(true)?;
+                false
             }
-            StatementKind::Loop(r#loop) => r#loop.body.last().returns,
             StatementKind::Block(block) => {
                 parser.end_statement(true)?;
                 match block.body.last() {
@@ -98,6 +151,10 @@ impl Instruction for Statement {
                     None => false,
                 }
             }
+            StatementKind::RawBlock(raw_block) => {
+                parser.end_statement(true)?;
+                raw_block.body.last().returns
+            }
             _ => parser.end_statement(false)?,
         };
         Ok(Statement {
@@ -107,33 +164,75 @@ impl Instruction for Statement {
         })
     }
 
-    fn check(&self, type_checker: &mut TypeChecker) -> Result<Type, TypeCheckError> {
-        let result = match &self.kind {
+    fn check(&mut self, type_checker: &mut TypeChecker) -> Result<Type, TypeCheckError> {
+        let block = matches!(self.kind, StatementKind::Block(_));
+        if block {
+            type_checker.build_symbol_table(self)?;
+        }
+        let result = match &mut self.kind {
             StatementKind::Module { ast, .. } => {
                 for statement in ast {
                     statement.check(type_checker)?;
                 }
-                Ok(Type::Void)
+                Ok(Type::UNIT)
             }
 
             StatementKind::FunctionDeclaration(function_declaration) => {
                 function_declaration.check(type_checker)
             }
             StatementKind::FunctionCall(function_call) => function_call.check(type_checker),
+            StatementKind::ConstDeclaration(const_declaration) => {
+                const_declaration.check(type_checker)
+            }
+            StatementKind::ExternBlock(extern_block) => extern_block.check(type_checker),
+            StatementKind::StructDeclaration(struct_declaration) => {
+                struct_declaration.check(type_checker)
+            }
+            StatementKind::Impl(r#impl) => r#impl.check(type_checker),
+
+            StatementKind::Variable(variable) => variable.check(type_checker),
+            StatementKind::Let(r#let) => r#let.check(type_checker),
+
             StatementKind::Intrinsic(intrinsic) => intrinsic.check(type_checker),
+            StatementKind::Print(print) => print.check(type_checker),
+            StatementKind::Panic(panic) => panic.check(type_checker),
+            StatementKind::Assert(assert) => assert.check(type_checker),
 
             StatementKind::Use(r#use) => r#use.check(type_checker),
 
             StatementKind::StringLiteral(string_literal) => string_literal.check(type_checker),
+            StatementKind::ByteStringLiteral(byte_string_literal) => {
+                byte_string_literal.check(type_checker)
+            }
+            StatementKind::CStringLiteral(c_string_literal) => c_string_literal.check(type_checker),
+            StatementKind::CharLiteral(char_literal) => char_literal.check(type_checker),
+            StatementKind::ByteLiteral(byte_literal) => byte_literal.check(type_checker),
+            StatementKind::BoolLiteral(bool_literal) => bool_literal.check(type_checker),
+            StatementKind::IntegerLiteral(integer_literal) => integer_literal.check(type_checker),
+            StatementKind::Tuple(tuple) => tuple.check(type_checker),
+
+            StatementKind::Cast(cast) => cast.check(type_checker),
+            StatementKind::Binary(binary) => binary.check(type_checker),
+            StatementKind::Negate(negate) => negate.check(type_checker),
+            StatementKind::Field(field) => field.check(type_checker),
+            StatementKind::MethodCall(method_call) => method_call.check(type_checker),
+            StatementKind::Slice(slice) => slice.check(type_checker),
+            StatementKind::Reference(reference) => reference.check(type_checker),
+            StatementKind::Deref(deref) => deref.check(type_checker),
+            StatementKind::Assignment(assignment) => assignment.check(type_checker),
 
             StatementKind::Loop(r#loop) => r#loop.check(type_checker),
-            StatementKind::Block(block) => {
-                type_checker.build_symbol_table(self);
-                block.check(type_checker)
-            }
+            StatementKind::Break(r#break) => r#break.check(type_checker),
+            StatementKind::Return(r#return) => r#return.check(type_checker),
+            StatementKind::Defer(defer) => defer.check(type_checker),
+            StatementKind::Block(block) => block.check(type_checker),
+            StatementKind::RawBlock(raw_block) => raw_block.check(type_checker),
 
-            StatementKind::Empty => Ok(Type::Void),
+            StatementKind::Empty => Ok(Type::UNIT),
         };
+        if block {
+            type_checker.finish_items();
+        }
         match result {
             Ok(r) => Ok(r),
             Err(e) => match e {
@@ -141,19 +240,128 @@ impl Instruction for Statement {
                 TypeCheckError::UnsafeUseNoToken => {
                     Err(TypeCheckError::UnsafeUse(self.location.clone()))
                 }
+                e @ TypeCheckError::UnsafeCall { .. } => Err(e),
+                TypeCheckError::UnsafeCallNoToken { declaration } => {
+                    Err(TypeCheckError::UnsafeCall {
+                        location: self.location.clone(),
+                        declaration,
+                    })
+                }
                 e @ TypeCheckError::UndeclaredFunction(_) => Err(e),
                 TypeCheckError::UndeclaredFunctionNoToken => {
                     Err(TypeCheckError::UndeclaredFunction(self.location.clone()))
                 }
+                e @ TypeCheckError::NotConstCall(_) => Err(e),
+                TypeCheckError::NotConstCallNoToken => {
+                    Err(TypeCheckError::NotConstCall(self.location.clone()))
+                }
+                e @ TypeCheckError::UndeclaredVariable(_) => Err(e),
+                TypeCheckError::UndeclaredVariableNoToken => {
+                    Err(TypeCheckError::UndeclaredVariable(self.location.clone()))
+                }
+                e @ TypeCheckError::UndeclaredType { .. } => Err(e),
+                e @ TypeCheckError::UnknownTrait(_) => Err(e),
+                e @ TypeCheckError::InvalidDrop(_) => Err(e),
+                e @ TypeCheckError::UseOfMovedValue { .. } => Err(e),
+                e @ TypeCheckError::MoveOutOfDrop(_) => Err(e),
+                e @ TypeCheckError::MoveOutOfReference(_) => Err(e),
+                TypeCheckError::MoveOutOfReferenceNoToken => {
+                    Err(TypeCheckError::MoveOutOfReference(self.location.clone()))
+                }
+                e @ TypeCheckError::MoveOutOfStatic(_) => Err(e),
+                e @ TypeCheckError::NotAPlace(_) => Err(e),
+                e @ TypeCheckError::ImmutablePlace(_) => Err(e),
+                e @ TypeCheckError::BorrowConflict { .. } => Err(e),
+                e @ TypeCheckError::DoesNotLiveLongEnough { .. } => Err(e),
+                e @ TypeCheckError::ReturnsLocalReference(_) => Err(e),
+                e @ TypeCheckError::ReferenceInStruct(_) => Err(e),
+                e @ TypeCheckError::BreakOutsideLoop(_) => Err(e),
+                TypeCheckError::BreakOutsideLoopNoToken => {
+                    Err(TypeCheckError::BreakOutsideLoop(self.location.clone()))
+                }
+                e @ TypeCheckError::LeavesDefer(_) => Err(e),
+                TypeCheckError::LeavesDeferNoToken => {
+                    Err(TypeCheckError::LeavesDefer(self.location.clone()))
+                }
+                e @ TypeCheckError::ReturnOutsideFunction(_) => Err(e),
+                TypeCheckError::ReturnOutsideFunctionNoToken => {
+                    Err(TypeCheckError::ReturnOutsideFunction(self.location.clone()))
+                }
+                e @ TypeCheckError::MainReturnType(_) => Err(e),
+                e @ TypeCheckError::InvalidPanicHandler(_) => Err(e),
+                e @ TypeCheckError::DuplicatePanicHandler { .. } => Err(e),
+                e @ TypeCheckError::Redefinition { .. } => Err(e),
+                e @ TypeCheckError::UnresolvedImport(_) => Err(e),
+                TypeCheckError::UnresolvedImportNoToken => {
+                    Err(TypeCheckError::UnresolvedImport(self.location.clone()))
+                }
+                e @ TypeCheckError::PrivateItem(_) => Err(e),
+
+                e @ TypeCheckError::UnknownAttribute(_) => Err(e),
+                e @ TypeCheckError::MalformedAttribute(_) => Err(e),
+                e @ TypeCheckError::DuplicateAttribute { .. } => Err(e),
+                e @ TypeCheckError::MisplacedAttribute { .. } => Err(e),
+                e @ TypeCheckError::NotPrintable { .. } => Err(e),
 
                 e @ TypeCheckError::MismatchedType { .. } => Err(e),
+                e @ TypeCheckError::InvalidCast { .. } => Err(e),
+                TypeCheckError::InvalidCastNoToken { from, to } => {
+                    Err(TypeCheckError::InvalidCast {
+                        from,
+                        to,
+                        location: self.location.clone(),
+                    })
+                }
+                e @ TypeCheckError::LiteralOutOfRange { .. } => Err(e),
+                e @ TypeCheckError::InvalidOperand { .. } => Err(e),
+                e @ TypeCheckError::NegateUnsigned { .. } => Err(e),
+                e @ TypeCheckError::NoField { .. } => Err(e),
+                TypeCheckError::NoFieldNoToken { r#type, index } => Err(TypeCheckError::NoField {
+                    r#type,
+                    index,
+                    location: self.location.clone(),
+                }),
+                e @ TypeCheckError::MoveOutOfField { .. } => Err(e),
+                TypeCheckError::MoveOutOfFieldNoToken { r#type, index } => {
+                    Err(TypeCheckError::MoveOutOfField {
+                        r#type,
+                        index,
+                        location: self.location.clone(),
+                    })
+                }
+                e @ TypeCheckError::NoMethod { .. } => Err(e),
+                TypeCheckError::NoMethodNoToken { r#type, name } => Err(TypeCheckError::NoMethod {
+                    r#type,
+                    name,
+                    location: self.location.clone(),
+                }),
+                e @ TypeCheckError::NotSliceable { .. } => Err(e),
+                TypeCheckError::NotSliceableNoToken { r#type } => {
+                    Err(TypeCheckError::NotSliceable {
+                        r#type,
+                        location: self.location.clone(),
+                    })
+                }
+                e @ TypeCheckError::TupleArity { .. } => Err(e),
+                e @ TypeCheckError::ArgumentCount { .. } => Err(e),
+                TypeCheckError::ArgumentCountNoToken {
+                    expected,
+                    actual,
+                    variadic,
+                } => Err(TypeCheckError::ArgumentCount {
+                    expected,
+                    actual,
+                    variadic,
+                    location: self.location.clone(),
+                }),
             },
         }
     }
 
     fn gen_ir(&self, ir_generator: &mut IrGenerator) {
         match &self.kind {
-            StatementKind::Module { ast, .. } => {
+            StatementKind::Module { doc, ast, .. } => {
+                ir_generator.doc = doc.clone();
                 for statement in ast {
                     statement.gen_ir(ir_generator);
                     let stash = &ir_generator.pop_stash();
@@ -164,39 +372,441 @@ impl Instruction for Statement {
             StatementKind::FunctionDeclaration(function_declaration) => {
                 function_declaration.gen_ir(ir_generator)
             }
-            StatementKind::FunctionCall(function_call) => {
-                function_call.gen_ir(ir_generator);
-                if !self.returns {
-                    ir_generator.stash = format!(
-                        "%{} = {}",
-                        ir_generator.new_value(),
-                        ir_generator.pop_stash()
-                    );
-                }
+            StatementKind::FunctionCall(function_call) => function_call.gen_ir(ir_generator),
+            StatementKind::ConstDeclaration(const_declaration) => {
+                const_declaration.gen_ir(ir_generator)
             }
+            StatementKind::ExternBlock(extern_block) => extern_block.gen_ir(ir_generator),
+            StatementKind::StructDeclaration(struct_declaration) => {
+                struct_declaration.gen_ir(ir_generator)
+            }
+            StatementKind::Impl(r#impl) => r#impl.gen_ir(ir_generator),
+
+            StatementKind::Variable(variable) => variable.gen_ir(ir_generator),
+            StatementKind::Let(r#let) => r#let.gen_ir(ir_generator),
+
             StatementKind::Intrinsic(intrinsic) => intrinsic.gen_ir(ir_generator),
+            StatementKind::Print(print) => print.gen_ir(ir_generator),
+            StatementKind::Panic(panic) => panic.gen_ir(ir_generator),
+            StatementKind::Assert(assert) => assert.gen_ir(ir_generator),
 
             StatementKind::Use(r#use) => r#use.gen_ir(ir_generator),
 
             StatementKind::StringLiteral(string_literal) => string_literal.gen_ir(ir_generator),
+            StatementKind::ByteStringLiteral(byte_string_literal) => {
+                byte_string_literal.gen_ir(ir_generator)
+            }
+            StatementKind::CStringLiteral(c_string_literal) => {
+                c_string_literal.gen_ir(ir_generator)
+            }
+            StatementKind::CharLiteral(char_literal) => char_literal.gen_ir(ir_generator),
+            StatementKind::ByteLiteral(byte_literal) => byte_literal.gen_ir(ir_generator),
+            StatementKind::BoolLiteral(bool_literal) => bool_literal.gen_ir(ir_generator),
+            StatementKind::IntegerLiteral(integer_literal) => integer_literal.gen_ir(ir_generator),
+            StatementKind::Tuple(tuple) => tuple.gen_ir(ir_generator),
+
+            StatementKind::Cast(cast) => cast.gen_ir(ir_generator),
+            StatementKind::Binary(binary) => binary.gen_ir(ir_generator),
+            StatementKind::Negate(negate) => negate.gen_ir(ir_generator),
+            StatementKind::Field(field) => field.gen_ir(ir_generator),
+            StatementKind::MethodCall(method_call) => method_call.gen_ir(ir_generator),
+            StatementKind::Slice(slice) => slice.gen_ir(ir_generator),
+            StatementKind::Reference(reference) => reference.gen_ir(ir_generator),
+            StatementKind::Deref(deref) => deref.gen_ir(ir_generator),
+            StatementKind::Assignment(assignment) => assignment.gen_ir(ir_generator),
 
             StatementKind::Loop(r#loop) => r#loop.gen_ir(ir_generator),
+            StatementKind::Break(r#break) => r#break.gen_ir(ir_generator),
+            StatementKind::Return(r#return) => r#return.gen_ir(ir_generator),
+            StatementKind::Defer(defer) => defer.gen_ir(ir_generator),
             StatementKind::Block(block) => block.gen_ir(ir_generator),
+            StatementKind::RawBlock(raw_block) => raw_block.gen_ir(ir_generator),
 
             StatementKind::Empty => (),
         }
+    }
 
-        if self.returns && std::ptr::eq(self.last(), self) {
-            ir_generator.stash = format!(
-                "%{} = {}",
-                ir_generator.new_value(),
-                ir_generator.pop_stash()
-            );
-        }
+    // Every statement is a step, so evaluation that does not end runs out of them
+    fn evaluate(&self, evaluator: &mut Evaluator) -> Result<Value, Interrupt> {
+        evaluator.step(&self.location)?;
+        let result = match &self.kind {
+            StatementKind::FunctionDeclaration(_)
+            | StatementKind::ConstDeclaration(_)
+            | StatementKind::ExternBlock(_)
+            | StatementKind::StructDeclaration(_)
+            | StatementKind::Impl(_)
+            | StatementKind::Use(_)
+            | StatementKind::Empty => Ok(Value::UNIT),
+            StatementKind::Module { .. } => unreachable!(),
+
+            StatementKind::FunctionCall(function_call) => function_call.evaluate(evaluator),
+
+            StatementKind::Variable(variable) => variable.evaluate(evaluator),
+            StatementKind::Let(r#let) => r#let.evaluate(evaluator),
+
+            StatementKind::Intrinsic(intrinsic) => intrinsic.evaluate(evaluator),
+            StatementKind::Print(print) => print.evaluate(evaluator),
+            StatementKind::Panic(panic) => panic.evaluate(evaluator),
+            StatementKind::Assert(assert) => assert.evaluate(evaluator),
+
+            StatementKind::StringLiteral(string_literal) => string_literal.evaluate(evaluator),
+            StatementKind::ByteStringLiteral(byte_string_literal) => {
+                byte_string_literal.evaluate(evaluator)
+            }
+            StatementKind::CStringLiteral(c_string_literal) => c_string_literal.evaluate(evaluator),
+            StatementKind::CharLiteral(char_literal) => char_literal.evaluate(evaluator),
+            StatementKind::ByteLiteral(byte_literal) => byte_literal.evaluate(evaluator),
+            StatementKind::BoolLiteral(bool_literal) => bool_literal.evaluate(evaluator),
+            StatementKind::IntegerLiteral(integer_literal) => integer_literal.evaluate(evaluator),
+            StatementKind::Tuple(tuple) => tuple.evaluate(evaluator),
+
+            StatementKind::Cast(cast) => cast.evaluate(evaluator),
+            StatementKind::Binary(binary) => binary.evaluate(evaluator),
+            StatementKind::Negate(negate) => negate.evaluate(evaluator),
+            StatementKind::Field(field) => field.evaluate(evaluator),
+            StatementKind::MethodCall(method_call) => method_call.evaluate(evaluator),
+            StatementKind::Slice(slice) => slice.evaluate(evaluator),
+            StatementKind::Reference(reference) => reference.evaluate(evaluator),
+            StatementKind::Deref(deref) => deref.evaluate(evaluator),
+            StatementKind::Assignment(assignment) => assignment.evaluate(evaluator),
+
+            StatementKind::Loop(r#loop) => r#loop.evaluate(evaluator),
+            StatementKind::Break(r#break) => r#break.evaluate(evaluator),
+            StatementKind::Return(r#return) => r#return.evaluate(evaluator),
+            StatementKind::Defer(defer) => defer.evaluate(evaluator),
+            StatementKind::Block(block) => block.evaluate(evaluator),
+            StatementKind::RawBlock(raw_block) => raw_block.evaluate(evaluator),
+        };
+        result.map_err(|interrupt| match interrupt {
+            Interrupt::Error(EvaluationError::NotConstNoToken) => {
+                EvaluationError::NotConst(self.location.clone()).into()
+            }
+            Interrupt::Error(EvaluationError::OutOfBoundsNoToken { length }) => {
+                EvaluationError::OutOfBounds {
+                    length,
+                    location: self.location.clone(),
+                }
+                .into()
+            }
+            Interrupt::Error(EvaluationError::RecursionLimitNoToken) => {
+                EvaluationError::RecursionLimit(self.location.clone()).into()
+            }
+            Interrupt::Error(EvaluationError::PanicNoToken { message }) => EvaluationError::Panic {
+                message,
+                location: self.location.clone(),
+            }
+            .into(),
+            interrupt => interrupt,
+        })
     }
 }
 
 impl Statement {
+    // `+` and `-` bind looser than `*`, `/` and `%`, which bind looser than casts, which bind
+    // looser than `&` and `*`, and an assignment takes everything after the `=`
+    pub fn parse_expression(parser: &mut Parser) -> Result<Self, ParseError> {
+        let mut expression = Statement::parse_sum(parser)?;
+        if let Some(TokenKind::Equals) = parser.peek().map(|token| token.kind) {
+            let mut assignment = Assignment::parse(parser)?;
+            assignment.place = Box::new(expression.clone());
+            expression = Statement {
+                kind: StatementKind::Assignment(assignment),
+                location: expression.location.to(&parser.previous().location),
+                returns: false,
+            };
+        }
+        Ok(expression)
+    }
+
+    fn parse_sum(parser: &mut Parser) -> Result<Self, ParseError> {
+        let mut expression = Statement::parse_product(parser)?;
+        while let Some(TokenKind::Plus | TokenKind::Minus) = parser.peek().map(|token| token.kind)
+            && !expression.is_block_like()
+        {
+            expression = Statement::binary(parser, expression)?;
+        }
+        Ok(expression)
+    }
+
+    pub fn parse_product(parser: &mut Parser) -> Result<Self, ParseError> {
+        let mut expression = Statement::parse_cast(parser)?;
+        while let Some(TokenKind::Star | TokenKind::Slash | TokenKind::Percent) =
+            parser.peek().map(|token| token.kind)
+            && !expression.is_block_like()
+        {
+            expression = Statement::binary(parser, expression)?;
+        }
+        Ok(expression)
+    }
+
+    pub fn parse_cast(parser: &mut Parser) -> Result<Self, ParseError> {
+        let mut expression = Statement::parse_unary(parser)?;
+        while let Some(TokenKind::Keyword(Keyword::As)) = parser.peek().map(|token| token.kind) {
+            let mut cast = Cast::parse(parser)?;
+            cast.value = Box::new(expression.clone());
+            expression = Statement {
+                kind: StatementKind::Cast(cast),
+                location: expression.location.to(&parser.previous().location),
+                returns: false,
+            };
+        }
+        Ok(expression)
+    }
+
+    fn binary(parser: &mut Parser, left: Statement) -> Result<Self, ParseError> {
+        let mut binary = Binary::parse(parser)?;
+        let location = left.location.to(&parser.previous().location);
+        binary.left = Box::new(left);
+        binary.location = location.clone();
+        Ok(Statement {
+            kind: StatementKind::Binary(binary),
+            location,
+            returns: false,
+        })
+    }
+
+    // A statement that ends in a `}` ends there, so `{ ... } -1` is two statements
+    fn is_block_like(&self) -> bool {
+        matches!(
+            self.kind,
+            StatementKind::FunctionDeclaration(_)
+                | StatementKind::ConstDeclaration(_)
+                | StatementKind::ExternBlock(_)
+                | StatementKind::StructDeclaration(_)
+                | StatementKind::Impl(_)
+                | StatementKind::Loop(_)
+                | StatementKind::Defer(_)
+                | StatementKind::Block(_)
+                | StatementKind::RawBlock(_)
+        )
+    }
+
+    pub fn parse_unary(parser: &mut Parser) -> Result<Self, ParseError> {
+        // The expansion of a macro is parsed in place of its invocation
+        while let Some(TokenKind::Identifier(name)) = parser.peek().map(|token| token.kind)
+            && let Some(TokenKind::Bang) = parser.peek_nth(1).map(|token| token.kind)
+            && !parser.is_builtin_macro(&name)
+        {
+            parser.expand_macro()?;
+        }
+        let Some(token) = parser.peek() else {
+            return Err(ParseError::UnexpectedEOF);
+        };
+        let kind = match token.kind {
+            TokenKind::Ampersand => StatementKind::Reference(Reference::parse(parser)?),
+            TokenKind::Star => StatementKind::Deref(Deref::parse(parser)?),
+            // A minus sign in front of a literal is part of it, so `-128i8` is in range
+            TokenKind::Minus
+                if matches!(
+                    parser.peek_nth(1).map(|token| token.kind),
+                    Some(TokenKind::IntegerLiteral(_))
+                ) && !matches!(
+                    parser.peek_nth(2).map(|token| token.kind),
+                    Some(TokenKind::Dot | TokenKind::OpenBracket)
+                ) =>
+            {
+                StatementKind::IntegerLiteral(IntegerLiteral::parse(parser)?)
+            }
+            TokenKind::Minus => StatementKind::Negate(Negate::parse(parser)?),
+            _ => return Statement::parse_postfix(parser),
+        };
+        Ok(Statement {
+            kind,
+            location: token.location.to(&parser.previous().location),
+            returns: false,
+        })
+    }
+
+    fn parse_postfix(parser: &mut Parser) -> Result<Self, ParseError> {
+        let mut expression = Statement::parse_primary(parser)?;
+        loop {
+            let kind = match parser.peek().map(|token| token.kind) {
+                Some(TokenKind::Dot) => match parser.peek_nth(1).map(|token| token.kind) {
+                    Some(TokenKind::Identifier(_)) => {
+                        let mut method_call = MethodCall::parse(parser)?;
+                        method_call.value = Box::new(expression.clone());
+                        StatementKind::MethodCall(method_call)
+                    }
+                    _ => {
+                        let mut field = Field::parse(parser)?;
+                        field.value = Box::new(expression.clone());
+                        StatementKind::Field(field)
+                    }
+                },
+                Some(TokenKind::OpenBracket) => {
+                    let mut slice = Slice::parse(parser)?;
+                    slice.value = Box::new(expression.clone());
+                    slice.location = expression.location.to(&parser.previous().location);
+                    StatementKind::Slice(slice)
+                }
+                _ => break,
+            };
+            expression = Statement {
+                kind,
+                location: expression.location.to(&parser.previous().location),
+                returns: false,
+            };
+        }
+        Ok(expression)
+    }
+
+    fn parse_primary(parser: &mut Parser) -> Result<Self, ParseError> {
+        let token = if let Some(token) = parser.peek() {
+            token
+        } else {
+            return Err(ParseError::UnexpectedEOF);
+        };
+        let location = token.location.clone();
+
+        let kind = match token.kind {
+            TokenKind::Keyword(Keyword::Use) => StatementKind::Use(Use::parse(parser)?),
+            // `const` starts a constant unless it is a `const fn`
+            // Attributes belong to the item after them
+            TokenKind::Hash => match parser.peek_nth(parser.item_start()).map(|token| token.kind) {
+                Some(TokenKind::Keyword(Keyword::Struct)) => {
+                    StatementKind::StructDeclaration(StructDeclaration::parse(parser)?)
+                }
+                _ if parser.is_constant() => {
+                    StatementKind::ConstDeclaration(ConstDeclaration::parse(parser)?)
+                }
+                _ => StatementKind::FunctionDeclaration(FunctionDeclaration::parse(parser)?),
+            },
+            TokenKind::Keyword(Keyword::Pub | Keyword::Const | Keyword::Static)
+                if parser.is_constant() =>
+            {
+                StatementKind::ConstDeclaration(ConstDeclaration::parse(parser)?)
+            }
+            TokenKind::Keyword(Keyword::Raw)
+                if matches!(
+                    parser.peek_nth(1).map(|token| token.kind),
+                    Some(TokenKind::OpenBrace)
+                ) =>
+            {
+                StatementKind::RawBlock(RawBlock::parse(parser)?)
+            }
+            TokenKind::Keyword(Keyword::Pub | Keyword::Const | Keyword::Fn | Keyword::Raw) => {
+                StatementKind::FunctionDeclaration(FunctionDeclaration::parse(parser)?)
+            }
+            TokenKind::Keyword(Keyword::Extern) => {
+                StatementKind::ExternBlock(ExternBlock::parse(parser)?)
+            }
+            TokenKind::Keyword(Keyword::Struct) => {
+                StatementKind::StructDeclaration(StructDeclaration::parse(parser)?)
+            }
+            TokenKind::Keyword(Keyword::Impl) => StatementKind::Impl(Impl::parse(parser)?),
+            TokenKind::Keyword(Keyword::Loop) => StatementKind::Loop(Loop::parse(parser)?),
+            TokenKind::Keyword(Keyword::Break) => StatementKind::Break(Break::parse(parser)?),
+            TokenKind::Keyword(Keyword::Return) => StatementKind::Return(Return::parse(parser)?),
+            TokenKind::Keyword(Keyword::Defer) => StatementKind::Defer(Defer::parse(parser)?),
+            TokenKind::Keyword(Keyword::Let) => StatementKind::Let(Let::parse(parser)?),
+            TokenKind::Keyword(
+                Keyword::As | Keyword::Mut | Keyword::Static | Keyword::For | Keyword::Macro,
+            ) => Err(ParseError::UnexpectedToken {
+                actual: Box::new(token),
+                expected: ExpectedToken::Statement,
+            })?,
+
+            TokenKind::StringLiteral(_) => {
+                StatementKind::StringLiteral(StringLiteral::parse(parser)?)
+            }
+            TokenKind::ByteStringLiteral(_) => {
+                StatementKind::ByteStringLiteral(ByteStringLiteral::parse(parser)?)
+            }
+            TokenKind::CStringLiteral(_) => {
+                StatementKind::CStringLiteral(CStringLiteral::parse(parser)?)
+            }
+            TokenKind::CharLiteral(_) => StatementKind::CharLiteral(CharLiteral::parse(parser)?),
+            TokenKind::ByteLiteral(_) => StatementKind::ByteLiteral(ByteLiteral::parse(parser)?),
+            TokenKind::BoolLiteral(_) => StatementKind::BoolLiteral(BoolLiteral::parse(parser)?),
+            TokenKind::IntegerLiteral(_) => {
+                StatementKind::IntegerLiteral(IntegerLiteral::parse(parser)?)
+            }
+
+            TokenKind::Intrinsic(_) => StatementKind::Intrinsic(Intrinsic::parse(parser)?),
+            TokenKind::Identifier(name) => match parser.peek_nth(1) {
+                Some(Token {
+                    kind: TokenKind::OpenParen,
+                    ..
+                }) if parser.is_builtin_function(&name) => match name.as_str() {
+                    "panic" => StatementKind::Panic(Panic::parse(parser)?),
+                    _ => StatementKind::Assert(Assert::parse(parser)?),
+                },
+                Some(Token {
+                    kind: TokenKind::OpenParen,
+                    ..
+                }) => StatementKind::FunctionCall(FunctionCall::parse(parser)?),
+                // Only the macros built into the compiler are left after expansion
+                Some(Token {
+                    kind: TokenKind::Bang,
+                    ..
+                }) => StatementKind::Print(Print::parse(parser)?),
+                _ => StatementKind::Variable(Variable::parse(parser)?),
+            },
+            TokenKind::Type(_) => Err(ParseError::UnexpectedToken {
+                actual: Box::new(token),
+                expected: ExpectedToken::Statement,
+            })?,
+            TokenKind::PathSeparator => Err(ParseError::UnexpectedToken {
+                actual: Box::new(token),
+                expected: ExpectedToken::Statement,
+            })?,
+            TokenKind::Arrow | TokenKind::FatArrow => Err(ParseError::UnexpectedToken {
+                actual: Box::new(token),
+                expected: ExpectedToken::Statement,
+            })?,
+            TokenKind::Colon
+            | TokenKind::Comma
+            | TokenKind::Dot
+            | TokenKind::DotDot
+            | TokenKind::Ellipsis
+            | TokenKind::Equals => Err(ParseError::UnexpectedToken {
+                actual: Box::new(token),
+                expected: ExpectedToken::Statement,
+            })?,
+            TokenKind::OpenParen => {
+                let mut tuple = Tuple::parse(parser)?;
+                if tuple.grouping {
+                    return Ok(tuple.elements.pop().unwrap());
+                }
+                return Ok(Statement {
+                    kind: StatementKind::Tuple(tuple),
+                    location: location.to(&parser.previous().location),
+                    returns: false,
+                });
+            }
+            TokenKind::CloseParen => Err(ParseError::UnexpectedToken {
+                actual: Box::new(token),
+                expected: ExpectedToken::Statement,
+            })?,
+            TokenKind::OpenBrace => StatementKind::Block(Block::parse(parser)?),
+            TokenKind::CloseBrace => Err(ParseError::UnexpectedToken {
+                actual: Box::new(token),
+                expected: ExpectedToken::Statement,
+            })?,
+            TokenKind::OpenBracket
+            | TokenKind::CloseBracket
+            | TokenKind::Star
+            | TokenKind::Ampersand
+            | TokenKind::Bang
+            | TokenKind::Dollar
+            | TokenKind::Plus
+            | TokenKind::Minus
+            | TokenKind::Slash
+            | TokenKind::Percent => Err(ParseError::UnexpectedToken {
+                actual: Box::new(token),
+                expected: ExpectedToken::Statement,
+            })?,
+            TokenKind::Semicolon => StatementKind::Empty,
+            TokenKind::Trivia(_) => unreachable!(),
+        };
+
+        Ok(Statement {
+            kind,
+            location,
+            returns: false,
+        })
+    }
+
     pub const EMPTY: Self = Self {
         kind: StatementKind::Empty,
         location: Location {
@@ -204,6 +814,7 @@ impl Statement {
             col: 0,
             length: 0,
             source: [None, None, None],
+            expansion: None,
         },
         returns: false,
     };
@@ -218,17 +829,49 @@ impl Statement {
                 function_declaration.body.last()
             }
             StatementKind::FunctionCall(_) => self,
+            StatementKind::ConstDeclaration(_) => self,
+            StatementKind::ExternBlock(_) => self,
+            StatementKind::StructDeclaration(_) => self,
+            StatementKind::Impl(_) => self,
+
+            StatementKind::Variable(_) => self,
+            StatementKind::Let(_) => self,
+
             StatementKind::Intrinsic(_) => self,
+            StatementKind::Print(_) => self,
+            StatementKind::Panic(_) => self,
+            StatementKind::Assert(_) => self,
 
             StatementKind::Use(_) => self,
 
             StatementKind::StringLiteral(_) => self,
+            StatementKind::ByteStringLiteral(_) => self,
+            StatementKind::CStringLiteral(_) => self,
+            StatementKind::CharLiteral(_) => self,
+            StatementKind::ByteLiteral(_) => self,
+            StatementKind::BoolLiteral(_) => self,
+            StatementKind::IntegerLiteral(_) => self,
+            StatementKind::Tuple(_) => self,
+
+            StatementKind::Cast(_) => self,
+            StatementKind::Binary(_) => self,
+            StatementKind::Negate(_) => self,
+            StatementKind::Field(_) => self,
+            StatementKind::MethodCall(_) => self,
+            StatementKind::Slice(_) => self,
+            StatementKind::Reference(_) => self,
+            StatementKind::Deref(_) => self,
+            StatementKind::Assignment(_) => self,
 
             StatementKind::Loop(r#loop) => r#loop.body.last(),
+            StatementKind::Break(_) => self,
+            StatementKind::Return(_) => self,
+            StatementKind::Defer(_) => self,
             StatementKind::Block(block) => match block.body.last() {
                 Some(statement) => statement.last(),
                 None => &Statement::EMPTY,
             },
+            StatementKind::RawBlock(raw_block) => raw_block.body.last(),
             StatementKind::Empty => self,
         }
     }
@@ -238,6 +881,9 @@ pub struct Parser {
     module_name: String,
     tokens: Vec<Token>,
     pos: usize,
+    // The macros defined so far and how many times they were expanded
+    macros: HashMap<String, Rc<Macro>>,
+    expansions: usize,
 }
 
 impl Parser {
@@ -246,19 +892,82 @@ impl Parser {
             module_name,
             tokens,
             pos: 0,
+            macros: HashMap::new(),
+            expansions: 0,
         }
     }
 
+    // The module everything in this file is declared in
+    pub fn module_path(&self) -> Vec<String> {
+        self.module_name.split("::").map(String::from).collect()
+    }
+
     pub fn peek(&self) -> Option<Token> {
         self.tokens.get(self.pos).cloned()
     }
 
+    pub fn peek_nth(&self, n: usize) -> Option<Token> {
+        self.tokens.get(self.pos + n).cloned()
+    }
+
     pub fn bump(&mut self) -> Option<Token> {
         let token = self.peek();
         self.pos += 1;
         token
     }
 
+    // The token that was bumped last
+    pub fn previous(&self) -> &Token {
+        &self.tokens[self.pos - 1]
+    }
+
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    // The tokens from `start` up to the one bumped last, spaced the way Rust prints them rather
+    // than as they are written. `&`, `*`, `-` and `!` stick to what follows where they are not
+    // between two values
+    pub fn source_text(&self, start: usize) -> String {
+        let mut text = String::new();
+        let mut previous: Option<&TokenKind> = None;
+        let mut glued = true;
+        for token in &self.tokens[start..self.pos] {
+            let after_value = previous.is_some_and(ends_value);
+            let space = !glued
+                && match token.kind {
+                    TokenKind::CloseParen
+                    | TokenKind::CloseBracket
+                    | TokenKind::Comma
+                    | TokenKind::Semicolon
+                    | TokenKind::Colon
+                    | TokenKind::Dot
+                    | TokenKind::DotDot
+                    | TokenKind::PathSeparator => false,
+                    TokenKind::OpenParen | TokenKind::OpenBracket | TokenKind::Bang => !after_value,
+                    _ => true,
+                };
+            if space {
+                text.push(' ');
+            }
+            text.push_str(&token.location.value());
+            glued = match token.kind {
+                TokenKind::OpenParen
+                | TokenKind::OpenBracket
+                | TokenKind::Dot
+                | TokenKind::DotDot
+                | TokenKind::PathSeparator
+                | TokenKind::Hash
+                | TokenKind::Dollar
+                | TokenKind::Bang => true,
+                TokenKind::Ampersand | TokenKind::Star | TokenKind::Minus => !after_value,
+                _ => false,
+            };
+            previous = Some(&token.kind);
+        }
+        text
+    }
+
     pub fn expect(&mut self, expected: &TokenKind) -> Result<(), ParseError> {
         if let Some(token) = self.bump()
             && token.kind != *expected
@@ -294,6 +1003,160 @@ impl Parser {
         }
     }
 
+    // The `///` comments in front of the next token
+    pub fn doc_comments(&self) -> Vec<String> {
+        self.peek()
+            .map(|token| token.trivia)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|trivia| match trivia {
+                Trivia::Doc(doc) => Some(doc),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn expect_visibility(&mut self) -> bool {
+        if let Some(Token {
+            kind: TokenKind::Keyword(Keyword::Pub),
+            ..
+        }) = self.peek()
+        {
+            self.bump();
+            true
+        } else {
+            false
+        }
+    }
+
+    // How many tokens the attributes in front of the next item take up
+    pub fn item_start(&self) -> usize {
+        let mut n = 0;
+        while let Some(TokenKind::Hash) = self.peek_nth(n).map(|token| token.kind) {
+            // Attributes can not contain brackets, so the first `]` ends the attribute
+            while let Some(token) = self.peek_nth(n) {
+                n += 1;
+                if token.kind == TokenKind::CloseBracket {
+                    break;
+                }
+            }
+        }
+        n
+    }
+
+    // Whether the item that starts here is a `const` or `static` rather than a function
+    pub fn is_constant(&self) -> bool {
+        let mut start = self.item_start();
+        if let Some(TokenKind::Keyword(Keyword::Pub)) = self.peek_nth(start).map(|token| token.kind)
+        {
+            start += 1;
+        }
+        match self.peek_nth(start).map(|token| token.kind) {
+            Some(TokenKind::Keyword(Keyword::Static)) => true,
+            Some(TokenKind::Keyword(Keyword::Const)) => !matches!(
+                self.peek_nth(start + 1).map(|token| token.kind),
+                Some(TokenKind::Keyword(Keyword::Fn | Keyword::Raw))
+            ),
+            _ => false,
+        }
+    }
+
+    pub fn expect_type(&mut self) -> Result<Type, ParseError> {
+        match self.bump() {
+            Some(Token {
+                kind: TokenKind::Type(t),
+                ..
+            }) => Ok(t),
+            Some(Token {
+                kind: TokenKind::Identifier(name),
+                ..
+            }) => Ok(Type::Struct(Symbol::new(&self.module_path(), &name))),
+            Some(Token {
+                kind: TokenKind::Bang,
+                ..
+            }) => Ok(Type::Never),
+            // `(T)` only groups `T`, a tuple with one element is written `(T,)`
+            Some(Token {
+                kind: TokenKind::OpenParen,
+                ..
+            }) => {
+                let mut elements = Vec::new();
+                let mut trailing_comma = false;
+                while let Some(token) = self.peek()
+                    && token.kind != TokenKind::CloseParen
+                {
+                    elements.push(self.expect_type()?);
+                    trailing_comma = false;
+                    if let Some(Token {
+                        kind: TokenKind::Comma,
+                        ..
+                    }) = self.peek()
+                    {
+                        self.bump();
+                        trailing_comma = true;
+                    } else {
+                        break;
+                    }
+                }
+                self.expect(&TokenKind::CloseParen)?;
+                if elements.len() == 1 && !trailing_comma {
+                    return Ok(elements.pop().unwrap());
+                }
+                Ok(Type::Tuple(elements))
+            }
+            Some(Token {
+                kind: TokenKind::OpenBracket,
+                ..
+            }) => {
+                self.expect(&TokenKind::Type(Type::Int(IntType::U8)))?;
+                self.expect(&TokenKind::CloseBracket)?;
+                Ok(Type::ByteStr)
+            }
+            Some(Token {
+                kind: TokenKind::Star,
+                ..
+            }) => {
+                let mutable = match self.bump() {
+                    Some(Token {
+                        kind: TokenKind::Keyword(Keyword::Mut),
+                        ..
+                    }) => true,
+                    Some(Token {
+                        kind: TokenKind::Keyword(Keyword::Const),
+                        ..
+                    }) => false,
+                    Some(token) => Err(ParseError::UnexpectedToken {
+                        actual: Box::new(token),
+                        expected: ExpectedToken::PointerMutability,
+                    })?,
+                    None => Err(ParseError::UnexpectedEOF)?,
+                };
+                let pointee = Box::new(self.expect_type()?);
+                Ok(Type::Pointer { mutable, pointee })
+            }
+            Some(Token {
+                kind: TokenKind::Ampersand,
+                ..
+            }) => {
+                let mutable = self
+                    .peek()
+                    .is_some_and(|token| token.kind == TokenKind::Keyword(Keyword::Mut));
+                if mutable {
+                    self.bump();
+                }
+                let referent = Box::new(self.expect_type()?);
+                Ok(Type::Reference { mutable, referent })
+            }
+            Some(token) => Err(ParseError::UnexpectedToken {
+                actual: Box::new(token),
+                expected: ExpectedToken::Specific {
+                    kind: TokenKind::Type(Type::Any),
+                },
+            }),
+            _ => Err(ParseError::UnexpectedEOF),
+        }
+    }
+
     pub fn expect_optional_type(&mut self) -> Result<Type, ParseError> {
         let token = self.peek();
         if let Some(Token {
@@ -303,25 +1166,142 @@ impl Parser {
         {
             // Skip the arrow
             self.bump();
-            match self.bump() {
-                Some(Token {
-                    kind: TokenKind::Type(t),
-                    ..
-                }) => Ok(t),
-                Some(token) => Err(ParseError::UnexpectedToken {
-                    actual: Box::new(token),
-                    expected: ExpectedToken::Specific {
-                        kind: TokenKind::Type(Type::Any),
-                    },
-                }),
-                _ => Err(ParseError::UnexpectedEOF),
-            }
+            self.expect_type()
         } else {
-            Ok(Type::Void)
+            Ok(Type::UNIT)
+        }
+    }
+
+    // Parses `(name: type, ...)`, returns the parameters and whether the list ended in `...`
+    pub fn expect_parameters(
+        &mut self,
+        allow_variadic: bool,
+    ) -> Result<(Vec<Parameter>, bool), ParseError> {
+        self.expect(&TokenKind::OpenParen)?;
+        let mut parameters = Vec::new();
+        let mut variadic = false;
+        while let Some(token) = self.peek()
+            && token.kind != TokenKind::CloseParen
+        {
+            if token.kind == TokenKind::Ellipsis && allow_variadic {
+                self.bump();
+                variadic = true;
+                break;
+            }
+            let name = self.expect_ident()?;
+            self.expect(&TokenKind::Colon)?;
+            let start = self.peek().ok_or(ParseError::UnexpectedEOF)?.location;
+            let r#type = self.expect_type()?;
+            parameters.push(Parameter {
+                name,
+                r#type,
+                location: start.to(&self.previous().location),
+            });
+
+            if let Some(Token {
+                kind: TokenKind::Comma,
+                ..
+            }) = self.peek()
+            {
+                self.bump();
+            } else {
+                break;
+            }
         }
+        self.expect(&TokenKind::CloseParen)?;
+        Ok((parameters, variadic))
+    }
+
+    // `print!` is built into the compiler, unless the file defines a macro of that name
+    pub fn is_builtin_macro(&self, name: &str) -> bool {
+        name == "print" && !self.macros.contains_key(name)
+    }
+
+    // The functions built into the compiler, which are called like any other
+    pub fn is_builtin_function(&self, name: &str) -> bool {
+        matches!(name, "panic" | "assert" | "debug_assert")
+    }
+
+    pub fn define_macro(&mut self) -> Result<(), ParseError> {
+        let r#macro = Macro::parse(self)?;
+        self.macros.insert(r#macro.name.clone(), Rc::new(r#macro));
+        Ok(())
+    }
+
+    // Replaces the `name!(...)` that starts here with the tokens it expands to
+    pub fn expand_macro(&mut self) -> Result<(), ParseError> {
+        let start = self.pos;
+        let name = self.bump().unwrap();
+        self.bump();
+        let TokenKind::Identifier(ref identifier) = name.kind else {
+            unreachable!()
+        };
+        let Some(r#macro) = self.macros.get(identifier).cloned() else {
+            return Err(ParseError::UndefinedMacro(name.location));
+        };
+        let nesting = std::iter::successors(name.location.expansion.as_deref(), |expansion| {
+            expansion.invocation.expansion.as_deref()
+        })
+        .count();
+        if nesting >= DEPTH {
+            return Err(ParseError::MacroRecursionLimit(name.location));
+        }
+
+        let close = match self.bump().map(|token| token.kind) {
+            Some(TokenKind::OpenParen) => TokenKind::CloseParen,
+            Some(TokenKind::OpenBracket) => TokenKind::CloseBracket,
+            Some(TokenKind::OpenBrace) => TokenKind::CloseBrace,
+            Some(_) => Err(ParseError::UnexpectedToken {
+                actual: Box::new(self.previous().clone()),
+                expected: ExpectedToken::MacroDelimiter,
+            })?,
+            None => Err(ParseError::UnexpectedEOF)?,
+        };
+        let arguments_start = self.pos;
+        let mut depth = 0;
+        loop {
+            let token = self.peek().ok_or(ParseError::UnexpectedEOF)?;
+            match token.kind {
+                TokenKind::OpenParen | TokenKind::OpenBracket | TokenKind::OpenBrace => depth += 1,
+                TokenKind::CloseParen | TokenKind::CloseBracket | TokenKind::CloseBrace => {
+                    if depth == 0 {
+                        break;
+                    }
+                    depth -= 1
+                }
+                _ => (),
+            }
+            self.bump();
+        }
+        let arguments = self.tokens[arguments_start..self.pos].to_vec();
+        self.expect(&close)?;
+
+        let invocation = name.location.to(&self.previous().location);
+        let mut expansion = r#macro.expand(&arguments, &invocation, self.expansions)?;
+        self.expansions += 1;
+        // Comments in front of the invocation, like documentation, go in front of the expansion
+        if let Some(first) = expansion.first_mut() {
+            first.trivia.splice(0..0, name.trivia);
+        }
+        self.tokens.splice(start..self.pos, expansion);
+        self.pos = start;
+        Ok(())
+    }
+
+    pub fn expect_attributes(&mut self) -> Result<Vec<Attribute>, ParseError> {
+        let mut attributes = Vec::new();
+        while let Some(Token {
+            kind: TokenKind::Hash,
+            ..
+        }) = self.peek()
+        {
+            attributes.push(Attribute::parse(self)?);
+        }
+        Ok(attributes)
     }
 
     // Returns if the statement returns (does not end in a semicolon)
+    // Block like statements end in a `}` and do not need a semicolon
     pub fn end_statement(&mut self, block: bool) -> Result<bool, ParseError> {
         match self.peek() {
             Some(Token {
@@ -334,12 +1314,8 @@ impl Parser {
             Some(Token {
                 kind: TokenKind::CloseBrace,
                 ..
-            }) => {
-                if block {
-                    self.bump();
-                }
-                Ok(true)
-            }
+            }) => Ok(true),
+            _ if block => Ok(true),
             Some(token) => {
                 self.bump();
                 Err(ParseError::UnexpectedToken {
@@ -353,16 +1329,18 @@ impl Parser {
         }
     }
 
-    pub fn block_returns(&mut self) -> bool {
-        let mut i = 0;
-        while self.tokens.get(self.pos - i).unwrap().kind != TokenKind::CloseBrace {
-            i += 1;
-        }
-
-        self.tokens.get(self.pos - i).unwrap().kind != TokenKind::Semicolon
-    }
-
     pub fn parse(mut self) -> Result<Statement, ParseError> {
+        // `//!` comments have to come before the first item
+        let doc = self
+            .peek()
+            .map(|token| token.trivia)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|trivia| match trivia {
+                Trivia::InnerDoc(doc) => Some(doc),
+                _ => None,
+            })
+            .collect();
         let mut ast = Vec::new();
         while self.peek().is_some() {
             ast.push(Statement::parse(&mut self)?);
@@ -375,9 +1353,30 @@ impl Parser {
             },
             kind: StatementKind::Module {
                 name: self.module_name,
+                doc,
                 ast,
             },
             location: Location::default(),
         })
     }
 }
+
+// Whether an operator after the token is between two values rather than in front of one
+fn ends_value(kind: &TokenKind) -> bool {
+    matches!(
+        kind,
+        TokenKind::Identifier(_)
+            | TokenKind::Intrinsic(_)
+            | TokenKind::Type(_)
+            | TokenKind::BoolLiteral(_)
+            | TokenKind::IntegerLiteral(_)
+            | TokenKind::StringLiteral(_)
+            | TokenKind::ByteStringLiteral(_)
+            | TokenKind::CStringLiteral(_)
+            | TokenKind::CharLiteral(_)
+            | TokenKind::ByteLiteral(_)
+            | TokenKind::CloseParen
+            | TokenKind::CloseBracket
+            | TokenKind::CloseBrace
+    )
+}
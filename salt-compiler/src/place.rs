@@ -0,0 +1,66 @@
+// A place stands for memory rather than a value: a binding, a field of a place or what a
+// reference or pointer points to. It can be borrowed and assigned to
+
+use crate::{
+    error::TypeCheckError,
+    ir_generator::IrGenerator,
+    lexer::{Location, Type},
+    parser::{Statement, StatementKind},
+    type_checker::TypeChecker,
+};
+
+pub struct Place {
+    pub r#type: Type,
+    // The binding the place is in or the reference to it is held by, `None` for what a temporary
+    // points to
+    pub root: Option<usize>,
+    // The place is reached through a reference or pointer, so borrowing it does not borrow the
+    // binding itself
+    pub through_deref: bool,
+    pub mutable: bool,
+    // The name of the binding, or the expression the place is reached through
+    pub location: Location,
+}
+
+// A `const` is a value wherever it is used, like a literal
+pub fn is_place(statement: &Statement, type_checker: &TypeChecker) -> bool {
+    match &statement.kind {
+        StatementKind::Variable(variable) => !variable.is_const(type_checker),
+        StatementKind::Field(_) | StatementKind::Deref(_) => true,
+        _ => false,
+    }
+}
+
+// Checks an expression for where it is rather than for its value, so nothing is moved out of it
+pub fn check_place(
+    statement: &mut Statement,
+    type_checker: &mut TypeChecker,
+) -> Result<Place, TypeCheckError> {
+    let location = statement.location.clone();
+    let place = match &mut statement.kind {
+        StatementKind::Variable(variable) => variable.check_place(type_checker),
+        StatementKind::Field(field) => field.check_place(type_checker),
+        StatementKind::Deref(deref) => deref.check_place(type_checker),
+        _ => return Err(TypeCheckError::NotAPlace(location)),
+    };
+    // The place is not checked as a statement, so it does not get a location from there
+    place.map_err(|e| match e {
+        TypeCheckError::UndeclaredVariableNoToken => TypeCheckError::UndeclaredVariable(location),
+        TypeCheckError::NoFieldNoToken { r#type, index } => TypeCheckError::NoField {
+            r#type,
+            index,
+            location,
+        },
+        e => e,
+    })
+}
+
+// Generates the address of a place that was checked with `check_place`
+pub fn gen_address(statement: &Statement, ir_generator: &mut IrGenerator) -> String {
+    match &statement.kind {
+        StatementKind::Variable(variable) => variable.gen_address(ir_generator),
+        StatementKind::Field(field) => field.gen_address(ir_generator),
+        StatementKind::Deref(deref) => deref.gen_address(ir_generator),
+        _ => unreachable!(),
+    }
+}
@@ -0,0 +1,38 @@
+use crate::{
+    lexer,
+    parser::{Parser, Statement, StatementKind},
+    type_checker::FunctionSignature,
+};
+
+// The interfaces of the modules in `salt-stdlib`, the implementations are linked in
+pub fn module(path: &str) -> Option<Statement> {
+    let source = match path {
+        "std::io::serial" => include_str!("../../salt-stdlib/src/io/serial.salt"),
+        "std::io::vga" => include_str!("../../salt-stdlib/src/io/vga.salt"),
+        "std::panic" => include_str!("../../salt-stdlib/src/panic.salt"),
+        _ => return None,
+    };
+
+    let tokens = lexer::lex(&source.replace("\t", "    ")).expect("stdlib interfaces should lex");
+    let module = Parser::new(tokens, path.to_string())
+        .parse()
+        .expect("stdlib interfaces should parse");
+    Some(module)
+}
+
+// A function of a stdlib module that the compiler generates calls to
+pub fn function(path: &str, name: &str) -> FunctionSignature {
+    let StatementKind::Module { ast, .. } = module(path).expect("stdlib module should exist").kind
+    else {
+        unreachable!()
+    };
+    ast.iter()
+        .filter_map(|statement| match &statement.kind {
+            StatementKind::ExternBlock(extern_block) => Some(&extern_block.functions),
+            _ => None,
+        })
+        .flatten()
+        .find(|function| function.name == name)
+        .expect("stdlib function should be declared")
+        .signature()
+}
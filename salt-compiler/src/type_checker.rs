@@ -1,65 +1,1413 @@
 use std::collections::HashMap;
 
 use crate::{
+    error::{TypeCheckError, Warning},
     instruction::{block::Block, function_declaration::FunctionDeclaration},
+    lexer::{IntType, Location, Type},
+    mangle::Symbol,
     parser::{Statement, StatementKind},
+    stdlib,
 };
 
+#[derive(Debug, Clone)]
+pub struct FunctionSignature {
+    pub symbol: String,
+    pub parameters: Vec<Type>,
+    pub variadic: bool,
+    pub return_type: Type,
+    // Declared in an `extern` block, slices are passed as two arguments
+    pub c_abi: bool,
+    // A `const fn`, it can be called while constants are evaluated
+    pub constant: bool,
+    // A `raw fn`, it can only be called where unsafe operations are allowed
+    pub raw: bool,
+    // The name in the declaration
+    pub location: Location,
+}
+
+impl FunctionSignature {
+    pub fn declare_ir(&self) -> String {
+        format!(
+            "declare {} @{}({}){}\n",
+            self.return_type.return_ir(),
+            self.symbol,
+            self.parameters_ir(),
+            if self.return_type == Type::Never {
+                " noreturn"
+            } else {
+                ""
+            }
+        )
+    }
+
+    pub fn parameters_ir(&self) -> String {
+        let mut parameters = self
+            .parameters
+            .iter()
+            .map(|parameter| match parameter {
+                Type::Str | Type::ByteStr if self.c_abi => "i8*, i64".to_string(),
+                parameter => parameter.to_ir(),
+            })
+            .collect::<Vec<_>>();
+        if self.variadic {
+            parameters.push("...".to_string());
+        }
+        parameters.join(", ")
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StructSignature {
+    pub symbol: Symbol,
+    pub fields: Vec<Type>,
+    // The symbol of the `drop` function when the struct implements `Drop`
+    pub drop: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ConstantSignature {
+    pub symbol: String,
+    pub r#type: Type,
+    // A `static` has an address, a `const` is copied to where it is used
+    pub r#static: bool,
+}
+
+// The items of a block are declared in no particular order, a `use` after the others. The error
+// is at the one written later and the note at the one written first, `symbol` is set when the two
+// only clash in the symbol they are emitted under
+fn redefinition(
+    (location, imported): (Location, bool),
+    (previous, previous_imported): (Location, bool),
+    symbol: Option<&str>,
+) -> TypeCheckError {
+    let ((location, _), (previous, imported)) =
+        match (location.line, location.col) < (previous.line, previous.col) {
+            true => ((previous, previous_imported), (location, imported)),
+            false => ((location, imported), (previous, previous_imported)),
+        };
+    TypeCheckError::Redefinition {
+        location,
+        previous: Box::new(previous),
+        imported,
+        symbol: symbol.map(Box::from),
+    }
+}
+
+// Whether a value of the type has code to run when it goes out of scope
+pub fn needs_drop(structs: &HashMap<String, StructSignature>, t: &Type) -> bool {
+    match t {
+        Type::Tuple(elements) => elements.iter().any(|element| needs_drop(structs, element)),
+        Type::Struct(symbol) => {
+            let signature = &structs[&symbol.to_string()];
+            signature.drop.is_some()
+                || signature
+                    .fields
+                    .iter()
+                    .any(|field| needs_drop(structs, field))
+        }
+        _ => false,
+    }
+}
+
+// A binding in a function, where its value was moved out of it and whether it only lends the
+// value, like the value given to `drop`
+#[derive(Debug, Clone)]
+struct Local {
+    name: String,
+    r#type: Type,
+    mutable: bool,
+    moved: Option<Location>,
+    lent: bool,
+    // The loans the references in its value come from
+    derived: Vec<usize>,
+}
+
+// A borrow of a binding, or of something it points to when `through_deref` is set. A loan is
+// known by its index and lives as long as a reference that may come from it is in use
+#[derive(Debug, Clone)]
+struct Loan {
+    local: usize,
+    mutable: bool,
+    through_deref: bool,
+    location: Location,
+    // Where the binding went out of scope, a reference from the loan can not be used after it
+    expired: Option<Location>,
+}
+
+// How a place is used, decides which loans of it are in the way
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Access {
+    Read,
+    Move,
+    Write,
+    Borrow,
+    BorrowMut,
+}
+
+// Where the borrow state was when an expression started, see `TypeChecker::provenance`
+pub struct Mark {
+    in_flight: usize,
+    reads: usize,
+}
+
+// What a loop has to know to check moves out of it
+#[derive(Debug, Clone)]
+struct LoopScope {
+    breaks: bool,
+    // How many scopes were open and which bindings were moved before the loop
+    scopes: usize,
+    moved: Vec<bool>,
+    // How many loans and reads of bindings the function had before the loop
+    loans: usize,
+    reads: usize,
+    // A deferred block rather than a loop, `break` and `return` can not leave it
+    deferred: bool,
+}
+
+// An item a block declares or imports, by the key it has in the table of its kind
+#[derive(Debug, Clone)]
+struct Item {
+    key: String,
+    // The name in the declaration, or in the `use` that imports it
+    location: Location,
+    imported: bool,
+}
+
+// Functions, structs and constants are named apart, a block can have one of each of a name
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum ItemKind {
+    Function,
+    Struct,
+    Constant,
+}
+
+// What is emitted under a symbol, where it is declared and, for a function that is linked in
+// rather than defined in the module, its name and LLVM declaration
+#[derive(Debug, Clone)]
+struct Emitted {
+    location: Location,
+    imported: bool,
+    declaration: Option<(String, String)>,
+}
+
+// What a scope holds, it is cleaned up in the reverse order
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Scoped {
+    Local(usize),
+    // A deferred block by the index it is known by in its function
+    Defer(usize),
+}
+
+// What has to happen when a scope is left
+#[derive(Debug, Clone)]
+pub enum Cleanup {
+    // Dropping the value of a binding
+    Drop(usize, Type),
+    Defer(usize),
+}
+
 pub struct TypeChecker {
-    pub functions: HashMap<String, FunctionDeclaration>,
+    // Every function by its key, see `function_key`
+    pub functions: HashMap<String, FunctionSignature>,
+    // Every struct by the path of its symbol, and every constant by its key like a function
+    pub structs: HashMap<String, StructSignature>,
+    pub constants: HashMap<String, ConstantSignature>,
+    pub warnings: Vec<Warning>,
+    // The function with `#[panic_handler]` and where it is declared
+    pub panic_handler: Option<(String, Location)>,
+    module: Vec<String>,
+    // The items the module and each block that is being checked declare or import, by their
+    // kind and name to their key, innermost last
+    items: Vec<HashMap<(ItemKind, String), Item>>,
+    // The functions and constants being checked, outermost first, the items in them are nested
+    // under them, and how many items were nested under each path so far
+    path: Vec<String>,
+    nested: HashMap<String, usize>,
+    // Every function and `static` by the symbol it is emitted under
+    symbols: HashMap<String, Emitted>,
+    // Every binding of each function, a binding is known by its index
+    locals: Vec<Vec<Local>>,
+    // The bindings and deferred blocks of the scopes of each function that are open, innermost
+    // last, and how many deferred blocks each function has
+    scopes: Vec<Vec<Vec<Scoped>>>,
+    defers: Vec<usize>,
+    // Whether each function, and each `raw` block in it, allows unsafe operations, and how many
+    // it has
     in_raw_function: Vec<bool>,
+    unsafe_operations: Vec<usize>,
+    // Whether each function is a `const fn` or the value of a constant
+    in_const: Vec<bool>,
+    // The return type of each function and what required it
+    return_types: Vec<(Type, Location)>,
+    // The loops of each function that are being checked
+    loops: Vec<Vec<LoopScope>>,
+    // Every loan of each function, the loans of references in values that are not bound to
+    // anything yet and the bindings each function read, in order
+    loans: Vec<Vec<Loan>>,
+    in_flight: Vec<Vec<usize>>,
+    reads: Vec<Vec<usize>>,
+
+    // What each `Type::IntVar` was unified with and the expression that decided it
+    integers: Vec<Option<(Type, Location)>>,
+    // The first integer variable of each function and the literals that are range checked at its end,
+    // with whether they are negative, and the negated integers that have to be signed
+    function_integers: Vec<usize>,
+    literals: Vec<Vec<(u128, bool, Type, Location)>>,
+    negations: Vec<Vec<(Type, Location)>>,
 }
 
 impl TypeChecker {
-    pub fn new(ast: &Statement) -> Self {
-        let mut type_checker = Self {
+    pub fn new() -> Self {
+        Self {
             functions: HashMap::new(),
+            structs: HashMap::new(),
+            constants: HashMap::new(),
+            warnings: Vec::new(),
+            panic_handler: None,
+            module: Vec::new(),
+            items: Vec::new(),
+            path: Vec::new(),
+            nested: HashMap::new(),
+            symbols: HashMap::new(),
+            locals: Vec::new(),
+            scopes: Vec::new(),
+            defers: Vec::new(),
             in_raw_function: Vec::new(),
-        };
+            unsafe_operations: Vec::new(),
+            in_const: Vec::new(),
+            return_types: Vec::new(),
+            loops: Vec::new(),
+            loans: Vec::new(),
+            in_flight: Vec::new(),
+            reads: Vec::new(),
 
-        type_checker.build_symbol_table(ast);
-        type_checker
+            integers: Vec::new(),
+            function_integers: Vec::new(),
+            literals: Vec::new(),
+            negations: Vec::new(),
+        }
     }
 
-    pub fn build_symbol_table(&mut self, ast: &Statement) {
-        match &ast.kind {
-            StatementKind::Module { ast, .. } => {
-                for statement in ast {
-                    if let StatementKind::FunctionDeclaration(function_declaration) =
-                        &statement.kind
-                    {
-                        self.functions.insert(
-                            function_declaration.name.clone(),
-                            function_declaration.clone(),
-                        );
+    // Declares the items of the module or of a block, a name can only be defined once in each
+    pub fn build_symbol_table(&mut self, ast: &Statement) -> Result<(), TypeCheckError> {
+        let (body, top_level) = match &ast.kind {
+            StatementKind::Module { name, ast, .. } => {
+                self.module = name.split("::").map(String::from).collect();
+                (ast, true)
+            }
+            StatementKind::Block(Block { body, .. }) => (body, false),
+            _ => unreachable!(),
+        };
+        self.items.push(HashMap::new());
+        // Structs come first, so an `impl` and the types of other items can be written before the
+        // struct they use
+        let mut structs = Vec::new();
+        for statement in body {
+            if let StatementKind::StructDeclaration(struct_declaration) = &statement.kind {
+                let symbol = self
+                    .nested_symbol(&struct_declaration.name)
+                    .unwrap_or_else(|| Symbol::new(&self.module, &struct_declaration.name));
+                let key = symbol.to_string();
+                self.add_item(
+                    &struct_declaration.name,
+                    ItemKind::Struct,
+                    key.clone(),
+                    struct_declaration.location.clone(),
+                    false,
+                )?;
+                self.structs
+                    .insert(key.clone(), struct_declaration.signature(symbol));
+                structs.push(key);
+            }
+        }
+        for key in structs {
+            let fields = self.structs[&key]
+                .fields
+                .iter()
+                .map(|t| self.resolve_type(t))
+                .collect();
+            self.structs.get_mut(&key).unwrap().fields = fields;
+        }
+        for statement in body {
+            match &statement.kind {
+                StatementKind::Impl(r#impl) => {
+                    // An `impl` for a struct that is not declared is reported when it is checked
+                    let Some(key) = self.struct_key(&r#impl.r#type).cloned() else {
+                        continue;
+                    };
+                    let receiver = self.structs[&key].symbol.clone();
+                    for function in &r#impl.functions {
+                        let symbol = if function.no_mangle() {
+                            function.name.clone()
+                        } else {
+                            Symbol {
+                                receiver: Some(receiver.name.clone()),
+                                disambiguator: receiver.disambiguator,
+                                ..Symbol::new(&receiver.path, &function.name)
+                            }
+                            .mangle()
+                        };
+                        if r#impl.r#trait == "Drop" && function.name == "drop" {
+                            self.structs.get_mut(&key).unwrap().drop = Some(symbol.clone());
+                        }
+                        let method = format!("{key}::{}", function.name);
+                        if let Some(previous) = self.functions.get(&method) {
+                            return Err(redefinition(
+                                (function.location.clone(), false),
+                                (previous.location.clone(), false),
+                                None,
+                            ));
+                        }
+                        self.new_symbol(&symbol, &function.location, false, None)?;
+                        let signature = self.resolve_signature(function.signature(symbol));
+                        self.functions.insert(method, signature);
                     }
                 }
-            }
-            StatementKind::Block(Block { body, .. }) => {
-                for statement in body {
-                    if let StatementKind::FunctionDeclaration(function_declaration) =
-                        &statement.kind
+                StatementKind::FunctionDeclaration(function_declaration) => {
+                    let nested = self.nested_symbol(&function_declaration.name);
+                    let key = nested
+                        .as_ref()
+                        .map_or(function_declaration.name.clone(), Symbol::to_string);
+                    let symbol = if (top_level && function_declaration.name == "main")
+                        || function_declaration.no_mangle()
                     {
-                        self.functions.insert(
-                            function_declaration.name.clone(),
-                            function_declaration.clone(),
-                        );
+                        function_declaration.name.clone()
+                    } else {
+                        nested
+                            .unwrap_or_else(|| {
+                                Symbol::new(&self.module, &function_declaration.name)
+                            })
+                            .mangle()
+                    };
+                    let signature = self.resolve_signature(function_declaration.signature(symbol));
+                    self.declare_function(&function_declaration.name, key, signature)?;
+                }
+                StatementKind::ExternBlock(extern_block) => {
+                    for function in &extern_block.functions {
+                        let key = self
+                            .nested_symbol(&function.name)
+                            .map_or(function.name.clone(), |symbol| symbol.to_string());
+                        let signature = self.resolve_signature(function.signature());
+                        self.declare_function(&function.name, key, signature)?;
                     }
                 }
+                StatementKind::Use(r#use) => r#use.import(self).map_err(|error| match error {
+                    TypeCheckError::UnresolvedImportNoToken => {
+                        TypeCheckError::UnresolvedImport(statement.location.clone())
+                    }
+                    error => error,
+                })?,
+                StatementKind::ConstDeclaration(const_declaration) => {
+                    let nested = self.nested_symbol(&const_declaration.name);
+                    let key = nested
+                        .as_ref()
+                        .map_or(const_declaration.name.clone(), Symbol::to_string);
+                    let symbol = if const_declaration.no_mangle() {
+                        const_declaration.name.clone()
+                    } else {
+                        nested
+                            .unwrap_or_else(|| Symbol::new(&self.module, &const_declaration.name))
+                            .mangle()
+                    };
+                    self.add_item(
+                        &const_declaration.name,
+                        ItemKind::Constant,
+                        key.clone(),
+                        const_declaration.location.clone(),
+                        false,
+                    )?;
+                    if const_declaration.r#static {
+                        self.new_symbol(&symbol, &const_declaration.location, false, None)?;
+                    }
+                    let mut signature = const_declaration.signature(symbol);
+                    signature.r#type = self.resolve_type(&signature.r#type);
+                    self.constants.insert(key, signature);
+                }
+                _ => (),
             }
-            _ => unreachable!(),
+        }
+        Ok(())
+    }
+
+    // A written struct type is the struct of that name in the innermost block that has one, other
+    // types are left alone and unknown structs are reported by `check_type`
+    pub fn resolve_type(&self, t: &Type) -> Type {
+        match t {
+            Type::Pointer { mutable, pointee } => Type::Pointer {
+                mutable: *mutable,
+                pointee: Box::new(self.resolve_type(pointee)),
+            },
+            Type::Reference { mutable, referent } => Type::Reference {
+                mutable: *mutable,
+                referent: Box::new(self.resolve_type(referent)),
+            },
+            Type::Tuple(elements) => Type::Tuple(
+                elements
+                    .iter()
+                    .map(|element| self.resolve_type(element))
+                    .collect(),
+            ),
+            Type::Struct(symbol) => match self.struct_signature(&symbol.name) {
+                Some(signature) => Type::Struct(signature.symbol.clone()),
+                None => t.clone(),
+            },
+            t => t.clone(),
         }
     }
 
+    fn resolve_signature(&self, signature: FunctionSignature) -> FunctionSignature {
+        FunctionSignature {
+            parameters: signature
+                .parameters
+                .iter()
+                .map(|t| self.resolve_type(t))
+                .collect(),
+            return_type: self.resolve_type(&signature.return_type),
+            ..signature
+        }
+    }
+
+    // The symbol of an item declared in a block, nested under the functions and constants it is
+    // in. An item whose path was used before gets the next disambiguator, so two nested items of
+    // the same name never share a symbol. Items at the top of the module are not nested
+    fn nested_symbol(&mut self, name: &str) -> Option<Symbol> {
+        if self.path.is_empty() {
+            return None;
+        }
+        let mut path = self.module.clone();
+        path.extend(
+            self.path
+                .iter()
+                .flat_map(|item| item.split("::").map(String::from)),
+        );
+        let symbol = Symbol::new(&path, name);
+        let count = self.nested.entry(symbol.to_string()).or_insert(0);
+        *count += 1;
+        Some(Symbol {
+            disambiguator: *count - 1,
+            ..symbol
+        })
+    }
+
+    // Makes a function known by its name in the innermost block, where it shadows any function
+    // of that name from outside
+    pub fn declare_function(
+        &mut self,
+        name: &str,
+        key: String,
+        signature: FunctionSignature,
+    ) -> Result<(), TypeCheckError> {
+        let location = signature.location.clone();
+        self.add_function(name, key, signature, location, false)
+    }
+
+    // Makes a function of another module known like one declared where the `use` is
+    pub fn import_function(
+        &mut self,
+        name: &str,
+        key: String,
+        signature: FunctionSignature,
+        location: &Location,
+    ) -> Result<(), TypeCheckError> {
+        self.add_function(name, key, signature, location.clone(), true)
+    }
+
+    fn add_function(
+        &mut self,
+        name: &str,
+        key: String,
+        signature: FunctionSignature,
+        location: Location,
+        imported: bool,
+    ) -> Result<(), TypeCheckError> {
+        self.add_item(
+            name,
+            ItemKind::Function,
+            key.clone(),
+            location.clone(),
+            imported,
+        )?;
+        let declaration = signature
+            .c_abi
+            .then(|| (name.to_string(), signature.declare_ir()));
+        self.new_symbol(&signature.symbol, &location, imported, declaration)?;
+        self.functions.insert(key, signature);
+        Ok(())
+    }
+
+    fn add_item(
+        &mut self,
+        name: &str,
+        kind: ItemKind,
+        key: String,
+        location: Location,
+        imported: bool,
+    ) -> Result<(), TypeCheckError> {
+        let items = self.items.last_mut().unwrap();
+        if let Some(previous) = items.get(&(kind, name.to_string())) {
+            return Err(redefinition(
+                (location, imported),
+                (previous.location.clone(), previous.imported),
+                None,
+            ));
+        }
+        items.insert(
+            (kind, name.to_string()),
+            Item {
+                key,
+                location,
+                imported,
+            },
+        );
+        Ok(())
+    }
+
+    // A function of the stdlib the compiler generates calls to, it is known by its path
+    pub fn stdlib_function(
+        &mut self,
+        module: &str,
+        name: &str,
+        location: &Location,
+    ) -> Result<(), TypeCheckError> {
+        let key = format!("{module}::{name}");
+        if self.functions.contains_key(&key) {
+            return Ok(());
+        }
+        let signature = stdlib::function(module, name);
+        let declaration = Some((name.to_string(), signature.declare_ir()));
+        self.new_symbol(&signature.symbol, location, true, declaration)?;
+        self.functions.insert(key, signature);
+        Ok(())
+    }
+
+    // Two items can not be emitted under the same symbol, only the same function that is linked
+    // in can be declared more than once
+    fn new_symbol(
+        &mut self,
+        symbol: &str,
+        location: &Location,
+        imported: bool,
+        declaration: Option<(String, String)>,
+    ) -> Result<(), TypeCheckError> {
+        if let Some(previous) = self.symbols.get(symbol) {
+            if previous.declaration.is_some() && previous.declaration == declaration {
+                return Ok(());
+            }
+            return Err(redefinition(
+                (location.clone(), imported),
+                (previous.location.clone(), previous.imported),
+                Some(symbol),
+            ));
+        }
+        self.symbols.insert(
+            symbol.to_string(),
+            Emitted {
+                location: location.clone(),
+                imported,
+                declaration,
+            },
+        );
+        Ok(())
+    }
+
+    // Functions are known in the `functions` table by a key, the name of functions at the top of
+    // the module, the path of functions nested in blocks and of imported functions, and the type
+    // and name of functions in an `impl`. A name is resolved in the innermost block first
+    pub fn function_key(&self, name: &str) -> Option<&String> {
+        self.item_key(ItemKind::Function, name)
+    }
+
+    // Structs are known by the path of their symbol, constants by a key like functions
+    pub fn struct_key(&self, name: &str) -> Option<&String> {
+        self.item_key(ItemKind::Struct, name)
+    }
+
+    pub fn constant_key(&self, name: &str) -> Option<&String> {
+        self.item_key(ItemKind::Constant, name)
+    }
+
+    fn item_key(&self, kind: ItemKind, name: &str) -> Option<&String> {
+        let name = (kind, name.to_string());
+        self.items
+            .iter()
+            .rev()
+            .find_map(|items| items.get(&name))
+            .map(|item| &item.key)
+    }
+
+    pub fn struct_signature(&self, name: &str) -> Option<&StructSignature> {
+        self.structs.get(self.struct_key(name)?)
+    }
+
+    // Ends the block whose symbol table was built last
+    pub fn finish_items(&mut self) {
+        self.items.pop();
+    }
+
     pub fn new_function(&mut self, function: &FunctionDeclaration) {
-        self.in_raw_function.push(function.raw);
+        // The parameters are the outermost scope, `drop` only borrows the value it is given
+        let parameters = function
+            .parameters
+            .iter()
+            .map(|parameter| Local {
+                name: parameter.name.clone(),
+                r#type: parameter.r#type.clone(),
+                mutable: false,
+                moved: None,
+                lent: function.receiver.is_some(),
+                derived: Vec::new(),
+            })
+            .collect();
+        let name = match &function.receiver {
+            Some(receiver) => format!("{}::{}", receiver, function.name),
+            None => function.name.clone(),
+        };
+        self.enter(
+            name,
+            function.raw,
+            function.constant,
+            (
+                function.return_type.clone(),
+                function.return_type_location.clone(),
+            ),
+            parameters,
+        );
     }
 
-    pub fn finish_function(&mut self) {
+    // Makes the function the one that is called on a panic, there can only be one
+    pub fn new_panic_handler(
+        &mut self,
+        function: &FunctionDeclaration,
+    ) -> Result<(), TypeCheckError> {
+        if let Some((_, previous)) = &self.panic_handler {
+            return Err(TypeCheckError::DuplicatePanicHandler {
+                location: function.location.clone(),
+                previous: Box::new(previous.clone()),
+            });
+        }
+        let parameters = function
+            .parameters
+            .iter()
+            .map(|parameter| &parameter.r#type)
+            .collect::<Vec<_>>();
+        if function.receiver.is_some()
+            || parameters
+                != [
+                    &Type::Str,
+                    &Type::Str,
+                    &Type::Int(IntType::U32),
+                    &Type::Int(IntType::U32),
+                ]
+            || function.return_type != Type::Never
+        {
+            return Err(TypeCheckError::InvalidPanicHandler(
+                function.location.clone(),
+            ));
+        }
+        self.panic_handler = Some((function.key(), function.location.clone()));
+        Ok(())
+    }
+
+    // The value of a constant is checked like the body of a `const fn` without parameters
+    pub fn new_constant(&mut self, name: &str, t: &Type, location: &Location) {
+        self.enter(
+            name.to_string(),
+            false,
+            true,
+            (t.clone(), location.clone()),
+            Vec::new(),
+        );
+    }
+
+    fn enter(
+        &mut self,
+        name: String,
+        raw: bool,
+        constant: bool,
+        return_type: (Type, Location),
+        parameters: Vec<Local>,
+    ) {
+        self.path.push(name);
+        self.in_raw_function.push(raw);
+        self.unsafe_operations.push(0);
+        self.in_const.push(constant);
+        self.return_types.push(return_type);
+        self.loops.push(Vec::new());
+        self.loans.push(Vec::new());
+        self.in_flight.push(Vec::new());
+        self.reads.push(Vec::new());
+        self.function_integers.push(self.integers.len());
+        self.literals.push(Vec::new());
+        self.negations.push(Vec::new());
+        self.scopes
+            .push(vec![(0..parameters.len()).map(Scoped::Local).collect()]);
+        self.locals.push(parameters);
+        self.defers.push(0);
+    }
+
+    // Integers nothing constrained fall back to `i32`, after that every literal has its final type
+    pub fn finish_function(&mut self) -> Result<(), TypeCheckError> {
+        self.path.pop();
         self.in_raw_function.pop();
+        self.unsafe_operations.pop();
+        self.in_const.pop();
+        self.return_types.pop();
+        self.loops.pop();
+        self.loans.pop();
+        self.in_flight.pop();
+        self.reads.pop();
+        self.locals.pop();
+        self.scopes.pop();
+        self.defers.pop();
+
+        let start = self.function_integers.pop().unwrap();
+        for integer in start..self.integers.len() {
+            self.default_integer(&Type::IntVar(integer), &Location::default());
+        }
+        for (t, location) in self.negations.pop().unwrap() {
+            let t = self.resolve(&t);
+            if let Type::Int(int) = t
+                && !int.signed()
+            {
+                return Err(TypeCheckError::NegateUnsigned {
+                    location,
+                    r#type: Box::new(t),
+                });
+            }
+        }
+        for (value, negative, t, location) in self.literals.pop().unwrap() {
+            let t = self.resolve(&t);
+            let Type::Int(int) = t else {
+                continue;
+            };
+            if negative && !int.signed() {
+                return Err(TypeCheckError::NegateUnsigned {
+                    location,
+                    r#type: Box::new(t),
+                });
+            }
+            // Two's complement has one more negative value than positive ones
+            if value > int.max() + negative as u128 {
+                return Err(TypeCheckError::LiteralOutOfRange {
+                    r#type: Box::new(t),
+                    location,
+                });
+            }
+        }
+        Ok(())
     }
 
-    pub fn in_raw_function(&self) -> bool {
+    // Whether an unsafe operation is allowed here, asking counts as using the `raw` context
+    pub fn allows_unsafe(&mut self) -> bool {
+        *self.unsafe_operations.last_mut().unwrap() += 1;
         *self.in_raw_function.last().unwrap()
     }
+
+    // A `raw` block allows unsafe operations in it
+    pub fn new_raw(&mut self) {
+        self.in_raw_function.push(true);
+        self.unsafe_operations.push(0);
+    }
+
+    // Returns if there was an unsafe operation in the `raw` block
+    pub fn finish_raw(&mut self) -> bool {
+        self.in_raw_function.pop();
+        self.unsafe_operations.pop().unwrap() > 0
+    }
+
+    pub fn in_const(&self) -> bool {
+        self.in_const.last().copied().unwrap_or(false)
+    }
+
+    pub fn at_top_level(&self) -> bool {
+        self.in_raw_function.is_empty()
+    }
+
+    pub fn return_type(&self) -> Option<&(Type, Location)> {
+        self.return_types.last()
+    }
+
+    pub fn new_loop(&mut self) {
+        let loop_scope = LoopScope {
+            breaks: false,
+            scopes: self.scopes.last().unwrap().len(),
+            moved: self
+                .locals
+                .last()
+                .unwrap()
+                .iter()
+                .map(|local| local.moved.is_some())
+                .collect(),
+            loans: self.loans.last().unwrap().len(),
+            reads: self.reads.last().unwrap().len(),
+            deferred: false,
+        };
+        self.loops.last_mut().unwrap().push(loop_scope);
+    }
+
+    // Returns whether the loop has a `break`, a body that `repeats` must not move anything that
+    // was there before the loop, or leave a binding it reads with a reference to one of its own
+    pub fn finish_loop(&mut self, repeats: bool) -> Result<bool, TypeCheckError> {
+        let loop_scope = self.loops.last_mut().unwrap().pop().unwrap();
+        if repeats {
+            let locals = self.locals.last().unwrap();
+            for (local, moved) in locals.iter().zip(loop_scope.moved) {
+                if let Some(location) = &local.moved
+                    && !moved
+                {
+                    return Err(TypeCheckError::UseOfMovedValue {
+                        location: location.clone(),
+                        moved: None,
+                    });
+                }
+            }
+            let loans = self.loans.last().unwrap();
+            let reads = &self.reads.last().unwrap()[loop_scope.reads..];
+            for local in self.bindings().filter(|local| reads.contains(local)) {
+                let expired = locals[local]
+                    .derived
+                    .iter()
+                    .filter(|&&loan| loan >= loop_scope.loans)
+                    .find_map(|&loan| Some((&loans[loan].location, loans[loan].expired.as_ref()?)));
+                if let Some((loan, expired)) = expired
+                    && locals[local].moved.is_none()
+                {
+                    return Err(TypeCheckError::DoesNotLiveLongEnough {
+                        location: loan.clone(),
+                        dropped: Box::new(expired.clone()),
+                        used: None,
+                    });
+                }
+            }
+        }
+        Ok(loop_scope.breaks)
+    }
+
+    // Returns what has to be cleaned up before jumping out of the loop
+    pub fn new_break(&mut self) -> Result<Vec<Cleanup>, TypeCheckError> {
+        let Some(loop_scope) = self.loops.last_mut().and_then(|loops| loops.last_mut()) else {
+            return Err(TypeCheckError::BreakOutsideLoopNoToken);
+        };
+        if loop_scope.deferred {
+            return Err(TypeCheckError::LeavesDeferNoToken);
+        }
+        loop_scope.breaks = true;
+        let scopes = loop_scope.scopes;
+        Ok(self.cleanup(scopes))
+    }
+
+    // What has to be cleaned up before returning from the function
+    pub fn return_cleanup(&self) -> Result<Vec<Cleanup>, TypeCheckError> {
+        if self
+            .loops
+            .last()
+            .unwrap()
+            .iter()
+            .any(|loop_scope| loop_scope.deferred)
+        {
+            return Err(TypeCheckError::LeavesDeferNoToken);
+        }
+        Ok(self.cleanup(0))
+    }
+
+    // A deferred block runs while its scope is left, so it can not leave anything itself
+    pub fn new_defer(&mut self) {
+        let loop_scope = LoopScope {
+            breaks: false,
+            scopes: self.scopes.last().unwrap().len(),
+            moved: Vec::new(),
+            loans: self.loans.last().unwrap().len(),
+            reads: self.reads.last().unwrap().len(),
+            deferred: true,
+        };
+        self.loops.last_mut().unwrap().push(loop_scope);
+    }
+
+    // Adds the deferred block to the innermost scope and returns the index it is known by
+    pub fn finish_defer(&mut self) -> usize {
+        self.loops.last_mut().unwrap().pop();
+        let defer = self.defers.last_mut().unwrap();
+        *defer += 1;
+        let defer = *defer - 1;
+        self.scopes
+            .last_mut()
+            .unwrap()
+            .last_mut()
+            .unwrap()
+            .push(Scoped::Defer(defer));
+        defer
+    }
+
+    pub fn new_scope(&mut self) {
+        self.scopes.last_mut().unwrap().push(Vec::new());
+    }
+
+    // Closes the innermost scope at `end` and returns what has to be cleaned up there. The value
+    // of the scope can not borrow from the bindings that go away, a binding outside of it that
+    // does fails when it is used again
+    pub fn finish_scope(
+        &mut self,
+        end: &Location,
+        value: &Location,
+    ) -> Result<Vec<Cleanup>, TypeCheckError> {
+        let scopes = self.scopes.last().unwrap().len() - 1;
+        let cleanup = self.cleanup(scopes);
+        let closed = self.scopes.last_mut().unwrap().pop().unwrap();
+        let in_flight = self.in_flight.last().unwrap();
+        for (index, loan) in self.loans.last_mut().unwrap().iter_mut().enumerate() {
+            if !closed.contains(&Scoped::Local(loan.local)) || loan.through_deref {
+                continue;
+            }
+            if in_flight.contains(&index) {
+                return Err(TypeCheckError::DoesNotLiveLongEnough {
+                    location: loan.location.clone(),
+                    dropped: Box::new(end.clone()),
+                    used: Some(Box::new(value.clone())),
+                });
+            }
+            loan.expired = Some(end.clone());
+        }
+        Ok(cleanup)
+    }
+
+    // The deferred blocks of the scopes from `scopes` on and their bindings that still own a
+    // value that needs dropping, in the reverse of the order they were declared in
+    fn cleanup(&self, scopes: usize) -> Vec<Cleanup> {
+        let locals = self.locals.last().unwrap();
+        self.scopes.last().unwrap()[scopes..]
+            .iter()
+            .flatten()
+            .rev()
+            .filter_map(|scoped| match *scoped {
+                Scoped::Local(local) => {
+                    let t = &locals[local].r#type;
+                    let owns = locals[local].moved.is_none() && !locals[local].lent;
+                    (owns && self.needs_drop(t)).then(|| Cleanup::Drop(local, t.clone()))
+                }
+                Scoped::Defer(defer) => Some(Cleanup::Defer(defer)),
+            })
+            .collect()
+    }
+
+    // The bindings in the open scopes of the function, innermost last
+    fn bindings(&self) -> impl DoubleEndedIterator<Item = usize> + '_ {
+        self.scopes
+            .last()
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter_map(|scoped| match *scoped {
+                Scoped::Local(local) => Some(local),
+                Scoped::Defer(_) => None,
+            })
+    }
+
+    pub fn needs_drop(&self, t: &Type) -> bool {
+        needs_drop(&self.structs, &self.resolve(t))
+    }
+
+    pub fn constant(&self, name: &str) -> Option<&ConstantSignature> {
+        self.constants.get(self.constant_key(name)?)
+    }
+
+    // Finds the binding a name refers to, the innermost one wins
+    pub fn variable(&self, name: &str) -> Option<(usize, Type)> {
+        let locals = self.locals.last()?;
+        self.bindings()
+            .rev()
+            .find(|&local| locals[local].name == name)
+            .map(|local| (local, locals[local].r#type.clone()))
+    }
+
+    // Returns the index the new binding is known by
+    pub fn new_variable(&mut self, name: &str, t: Type, mutable: bool) -> usize {
+        let locals = self.locals.last_mut().unwrap();
+        locals.push(Local {
+            name: name.to_string(),
+            r#type: t,
+            mutable,
+            moved: None,
+            lent: false,
+            derived: Vec::new(),
+        });
+        let local = locals.len() - 1;
+        self.scopes
+            .last_mut()
+            .unwrap()
+            .last_mut()
+            .unwrap()
+            .push(Scoped::Local(local));
+        local
+    }
+
+    // Fails if the value of the binding was moved out of it already, or if it holds a reference
+    // to a binding that went out of scope. The references the expression being checked produces
+    // may come from the binding
+    pub fn use_variable(
+        &mut self,
+        local: usize,
+        location: &Location,
+    ) -> Result<(), TypeCheckError> {
+        self.reads.last_mut().unwrap().push(local);
+        let local = &self.locals.last().unwrap()[local];
+        if let Some(moved) = &local.moved {
+            return Err(TypeCheckError::UseOfMovedValue {
+                location: location.clone(),
+                moved: Some(Box::new(moved.clone())),
+            });
+        }
+        let loans = self.loans.last().unwrap();
+        match local
+            .derived
+            .iter()
+            .map(|&loan| &loans[loan])
+            .find_map(|loan| {
+                loan.expired
+                    .as_ref()
+                    .map(|expired| (&loan.location, expired))
+            }) {
+            Some((loan, expired)) => Err(TypeCheckError::DoesNotLiveLongEnough {
+                location: loan.clone(),
+                dropped: Box::new(expired.clone()),
+                used: Some(Box::new(location.clone())),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    // Uses the value of the binding, values that are not `Copy` can only be used once
+    pub fn move_variable(
+        &mut self,
+        local: usize,
+        location: &Location,
+    ) -> Result<(), TypeCheckError> {
+        self.use_variable(local, location)?;
+        let t = self.resolve(&self.locals.last().unwrap()[local].r#type);
+        if t.is_copy() {
+            return self.access(local, Access::Read, location);
+        }
+        self.access(local, Access::Move, location)?;
+        let local = &mut self.locals.last_mut().unwrap()[local];
+        if local.lent {
+            return Err(TypeCheckError::MoveOutOfDrop(location.clone()));
+        }
+        local.moved = Some(location.clone());
+        Ok(())
+    }
+
+    pub fn is_mutable(&self, local: usize) -> bool {
+        self.locals.last().unwrap()[local].mutable
+    }
+
+    pub fn is_moved(&self, local: usize) -> bool {
+        self.locals.last().unwrap()[local].moved.is_some()
+    }
+
+    // Assigning to a binding gives it a value again, with references that have nothing to do
+    // with its old value
+    pub fn reinitialize(&mut self, local: usize) {
+        let local = &mut self.locals.last_mut().unwrap()[local];
+        local.moved = None;
+        local.derived.clear();
+    }
+
+    // Fails if a loan of the binding that is still in use is in the way of `access`
+    pub fn access(
+        &self,
+        local: usize,
+        access: Access,
+        location: &Location,
+    ) -> Result<(), TypeCheckError> {
+        for (index, loan) in self.loans.last().unwrap().iter().enumerate() {
+            let conflicts = match access {
+                Access::Read | Access::Borrow => loan.mutable,
+                Access::Move | Access::Write | Access::BorrowMut => true,
+            };
+            if loan.local == local && conflicts && self.is_live(index) {
+                return Err(TypeCheckError::BorrowConflict {
+                    location: location.clone(),
+                    loan: Box::new(loan.location.clone()),
+                    access,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    // Borrows the binding, `location` is the expression that creates the reference. The borrow
+    // has to be checked with `access` first
+    pub fn new_loan(
+        &mut self,
+        local: usize,
+        mutable: bool,
+        through_deref: bool,
+        location: &Location,
+    ) {
+        let loans = self.loans.last_mut().unwrap();
+        loans.push(Loan {
+            local,
+            mutable,
+            through_deref,
+            location: location.clone(),
+            expired: None,
+        });
+        let loan = loans.len() - 1;
+        self.in_flight.last_mut().unwrap().push(loan);
+    }
+
+    // A loan is in use while a reference from it is not bound yet, or while a binding in scope
+    // that was not moved out of holds one
+    fn is_live(&self, loan: usize) -> bool {
+        let locals = self.locals.last().unwrap();
+        self.in_flight.last().unwrap().contains(&loan)
+            || self.bindings().any(|local| {
+                let local = &locals[local];
+                local.moved.is_none() && local.derived.contains(&loan)
+            })
+    }
+
+    pub fn mark(&self) -> Mark {
+        Mark {
+            in_flight: self.in_flight.last().unwrap().len(),
+            reads: self.reads.last().unwrap().len(),
+        }
+    }
+
+    // The loans the references in the value of the expression checked since `mark` may come
+    // from, the ones it created and the ones of the bindings it read
+    pub fn provenance(&self, mark: &Mark) -> Vec<usize> {
+        let locals = self.locals.last().unwrap();
+        let mut loans = self.in_flight.last().unwrap()[mark.in_flight..].to_vec();
+        for &local in &self.reads.last().unwrap()[mark.reads..] {
+            for &loan in &locals[local].derived {
+                if !loans.contains(&loan) {
+                    loans.push(loan);
+                }
+            }
+        }
+        loans
+    }
+
+    // The value of the expression checked since `mark` was used up, its references with it
+    pub fn finish_expression(&mut self, mark: &Mark) {
+        self.in_flight.last_mut().unwrap().truncate(mark.in_flight);
+    }
+
+    // The value of an expression lives on in the expression around it
+    pub fn escape(&mut self, loans: Vec<usize>) {
+        self.in_flight.last_mut().unwrap().extend(loans);
+    }
+
+    // The binding now holds references that may come from `loans`
+    pub fn derive(&mut self, local: usize, loans: Vec<usize>) {
+        self.locals.last_mut().unwrap()[local].derived.extend(loans);
+    }
+
+    // Fails if a reference that is returned may point into the function
+    pub fn check_return(&self, loans: &[usize]) -> Result<(), TypeCheckError> {
+        let all = self.loans.last().unwrap();
+        match loans
+            .iter()
+            .map(|&loan| &all[loan])
+            .find(|loan| !loan.through_deref)
+        {
+            Some(loan) => Err(TypeCheckError::ReturnsLocalReference(loan.location.clone())),
+            None => Ok(()),
+        }
+    }
+
+    // Resolves the structs a written type uses and reports the ones that are not declared
+    pub fn check_type(&self, t: &mut Type, location: &Location) -> Result<(), TypeCheckError> {
+        *t = self.resolve_type(t);
+        self.check_resolved(t, location)
+    }
+
+    fn check_resolved(&self, t: &Type, location: &Location) -> Result<(), TypeCheckError> {
+        match t {
+            Type::Pointer { pointee, .. } => self.check_resolved(pointee, location),
+            Type::Reference { referent, .. } => self.check_resolved(referent, location),
+            Type::Tuple(elements) => elements
+                .iter()
+                .try_for_each(|element| self.check_resolved(element, location)),
+            Type::Struct(symbol) if !self.structs.contains_key(&symbol.to_string()) => {
+                Err(TypeCheckError::UndeclaredType {
+                    name: symbol.name.as_str().into(),
+                    location: location.clone(),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    // The type of an integer literal, unsuffixed literals get theirs from how they are used
+    pub fn new_literal(
+        &mut self,
+        value: u128,
+        negative: bool,
+        suffix: Option<IntType>,
+        location: &Location,
+    ) -> Type {
+        let t = match suffix {
+            Some(int) => Type::Int(int),
+            None => {
+                self.integers.push(None);
+                Type::IntVar(self.integers.len() - 1)
+            }
+        };
+        self.literals
+            .last_mut()
+            .unwrap()
+            .push((value, negative, t.clone(), location.clone()));
+        t
+    }
+
+    // A negated integer whose type is not decided yet, it has to end up signed
+    pub fn new_negation(&mut self, t: &Type, location: &Location) {
+        self.negations
+            .last_mut()
+            .unwrap()
+            .push((t.clone(), location.clone()));
+    }
+
+    // Follows integer variables to the type they were unified with
+    pub fn resolve(&self, t: &Type) -> Type {
+        match t {
+            Type::IntVar(integer) => match &self.integers[*integer] {
+                Some((t, _)) => self.resolve(t),
+                None => t.clone(),
+            },
+            Type::Tuple(elements) => Type::Tuple(
+                elements
+                    .iter()
+                    .map(|element| self.resolve(element))
+                    .collect(),
+            ),
+            Type::Pointer { mutable, pointee } => Type::Pointer {
+                mutable: *mutable,
+                pointee: Box::new(self.resolve(pointee)),
+            },
+            Type::Reference { mutable, referent } => Type::Reference {
+                mutable: *mutable,
+                referent: Box::new(self.resolve(referent)),
+            },
+            t => t.clone(),
+        }
+    }
+
+    // Gives an integer that is still undecided the fallback type, for where it has to be known now
+    pub fn default_integer(&mut self, t: &Type, location: &Location) -> Type {
+        match self.resolve(t) {
+            Type::IntVar(integer) => {
+                self.integers[integer] = Some((Type::Int(IntType::I32), location.clone()));
+                Type::Int(IntType::I32)
+            }
+            t => t,
+        }
+    }
+
+    // An integer turned into a pointer falls back to `usize`, or to `isize` when it is negated, so
+    // any address can be written without a suffix
+    pub fn default_address(&mut self, t: &Type, location: &Location) -> Type {
+        let Type::IntVar(integer) = self.resolve(t) else {
+            return self.resolve(t);
+        };
+        let negative = self
+            .literals
+            .iter()
+            .flatten()
+            .filter(|(_, negative, _, _)| *negative)
+            .map(|(_, _, t, _)| t)
+            .chain(self.negations.iter().flatten().map(|(t, _)| t))
+            .any(|t| self.resolve(t) == Type::IntVar(integer));
+        let int = if negative {
+            IntType::Isize
+        } else {
+            IntType::Usize
+        };
+        self.integers[integer] = Some((Type::Int(int), location.clone()));
+        Type::Int(int)
+    }
+
+    // The expression that decided the type behind an integer variable
+    fn origin(&self, t: &Type) -> Option<Location> {
+        let Type::IntVar(integer) = t else {
+            return None;
+        };
+        let (t, location) = self.integers[*integer].as_ref()?;
+        self.origin(t).or_else(|| Some(location.clone()))
+    }
+
+    // Makes `actual` the same type as `expected`, `location` is the expression that has to fit
+    // and `origin` what required `expected`
+    pub fn unify(
+        &mut self,
+        expected: &Type,
+        actual: &Type,
+        location: &Location,
+        origin: Option<&Location>,
+    ) -> Result<(), TypeCheckError> {
+        let (expected_type, actual_type) = (self.resolve(expected), self.resolve(actual));
+        match (&expected_type, &actual_type) {
+            (_, Type::Never) => return Ok(()),
+            (Type::IntVar(a), Type::IntVar(b)) if a == b => return Ok(()),
+            (Type::IntVar(integer), t @ (Type::Int(_) | Type::IntVar(_)))
+            | (t @ Type::Int(_), Type::IntVar(integer)) => {
+                self.integers[*integer] = Some((t.clone(), location.clone()));
+                return Ok(());
+            }
+            (Type::Tuple(expected_elements), Type::Tuple(actual_elements))
+                if expected_elements.len() == actual_elements.len() =>
+            {
+                // Binds what can be bound, a conflict is reported for the whole tuple
+                let mut unified = true;
+                for (expected, actual) in expected_elements.iter().zip(actual_elements) {
+                    unified &= self.unify(expected, actual, location, origin).is_ok();
+                }
+                if unified {
+                    return Ok(());
+                }
+            }
+            (
+                Type::Reference {
+                    mutable: a,
+                    referent: expected,
+                },
+                Type::Reference {
+                    mutable: b,
+                    referent: actual,
+                },
+            )
+            | (
+                Type::Pointer {
+                    mutable: a,
+                    pointee: expected,
+                },
+                Type::Pointer {
+                    mutable: b,
+                    pointee: actual,
+                },
+            ) if a == b && self.unify(expected, actual, location, origin).is_ok() => {
+                return Ok(());
+            }
+            (a, b) if a == b => return Ok(()),
+            _ => (),
+        }
+
+        let (expected_type, actual_type) = (self.resolve(expected), self.resolve(actual));
+        // An integer variable was decided by an expression, that is what made it conflict
+        let expected_origin = self.origin(expected).or_else(|| origin.cloned());
+        let actual_origin = self.origin(actual).filter(|origin| origin != location);
+        Err(TypeCheckError::MismatchedType {
+            expected: Box::new((expected_type, expected_origin)),
+            actual: Box::new((actual_type, actual_origin)),
+            location: location.clone(),
+        })
+    }
+
+    // The type every integer variable ended up with, the IR generator has no use for variables
+    pub fn integer_types(&self) -> Vec<Type> {
+        (0..self.integers.len())
+            .map(|integer| self.resolve(&Type::IntVar(integer)))
+            .collect()
+    }
 }
@@ -1 +1,2 @@
-mod vga;
+pub mod serial;
+pub mod vga;
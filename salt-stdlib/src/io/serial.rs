@@ -0,0 +1,52 @@
+use core::arch::asm;
+
+// The first serial port, which QEMU forwards to the terminal with `-serial stdio`
+const COM1: u16 = 0x3F8;
+
+// The port is set up the first time something is written to it
+static mut INITIALIZED: bool = false;
+
+unsafe fn outb(port: u16, value: u8) {
+    unsafe { asm!("out dx, al", in("dx") port, in("al") value, options(nomem, nostack)) }
+}
+
+unsafe fn inb(port: u16) -> u8 {
+    let value;
+    unsafe { asm!("in al, dx", out("al") value, in("dx") port, options(nomem, nostack)) }
+    value
+}
+
+// 38400 baud, 8 data bits, no parity and one stop bit, with interrupts off
+unsafe fn init() {
+    unsafe {
+        outb(COM1 + 1, 0x00);
+        outb(COM1 + 3, 0x80);
+        outb(COM1, 0x03);
+        outb(COM1 + 1, 0x00);
+        outb(COM1 + 3, 0x03);
+        outb(COM1 + 2, 0xC7);
+        outb(COM1 + 4, 0x03);
+        INITIALIZED = true;
+    }
+}
+
+// Waits until the transmitter can take another byte
+unsafe fn put(byte: u8) {
+    unsafe {
+        while inb(COM1 + 5) & 0x20 == 0 {}
+        outb(COM1, byte);
+    }
+}
+
+// A Salt `str` is passed as a pointer to its first byte and its length in bytes
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn _salt_std_io_serial_print(string: *const u8, len: usize) {
+    unsafe {
+        if !INITIALIZED {
+            init();
+        }
+        for i in 0..len {
+            put(*string.add(i));
+        }
+    }
+}
@@ -1,13 +1,69 @@
-const VGA: *mut i8 = 0xB8000 as *mut i8;
+const VGA: *mut u8 = 0xB8000 as *mut u8;
+const CELLS: usize = 25 * 80;
+// Light grey on black
+const ATTRIBUTE: u8 = 0x07;
 
+// The cell the next character goes in, every print carries on where the last one stopped
+static mut CURSOR: usize = 0;
+
+unsafe fn put(byte: u8) {
+    unsafe {
+        if byte == b'\n' {
+            CURSOR += 80 - CURSOR % 80;
+        } else {
+            *VGA.add(CURSOR * 2) = byte;
+            *VGA.add(CURSOR * 2 + 1) = ATTRIBUTE;
+            CURSOR += 1;
+        }
+        CURSOR %= CELLS;
+    }
+}
+
+// A Salt `str` is passed as a pointer to its first byte and its length in bytes
 #[unsafe(no_mangle)]
-pub unsafe extern "C" fn _salt_std_io_vga_print(mut string: *const i8) {
+pub unsafe extern "C" fn _salt_std_io_vga_print(string: *const u8, len: usize) {
+    unsafe {
+        for i in 0..len {
+            put(*string.add(i));
+        }
+    }
+}
+
+// Prints the digits of `value` in `radix`, the most significant first
+unsafe fn put_digits(mut value: u64, radix: u64) {
+    let mut digits = [0; 20];
+    let mut len = 0;
+    loop {
+        digits[len] = b"0123456789abcdef"[(value % radix) as usize];
+        len += 1;
+        value /= radix;
+        if value == 0 {
+            break;
+        }
+    }
     unsafe {
-        let mut i = 0;
-        while *string != 0 && i < 25 * 80 {
-            *VGA.offset(i) = *string;
-            string = string.add(1);
-            i += 1;
+        for digit in digits[..len].iter().rev() {
+            put(*digit);
         }
     }
 }
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn _salt_std_io_vga_print_u64(value: u64) {
+    unsafe { put_digits(value, 10) }
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn _salt_std_io_vga_print_i64(value: i64) {
+    unsafe {
+        if value < 0 {
+            put(b'-');
+        }
+        put_digits(value.unsigned_abs(), 10)
+    }
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn _salt_std_io_vga_print_hex(value: u64) {
+    unsafe { put_digits(value, 16) }
+}
@@ -2,3 +2,4 @@
 #![no_main]
 
 mod io;
+mod panic;
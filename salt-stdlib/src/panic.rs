@@ -0,0 +1,53 @@
+use core::{arch::asm, slice};
+
+use crate::io::{serial, vga};
+
+// A panic is reported on the screen and on the serial port, in case the screen is not visible
+unsafe fn write(bytes: &[u8]) {
+    unsafe {
+        vga::_salt_std_io_vga_print(bytes.as_ptr(), bytes.len());
+        serial::_salt_std_io_serial_print(bytes.as_ptr(), bytes.len());
+    }
+}
+
+unsafe fn write_u32(mut value: u32) {
+    let mut digits = [0; 10];
+    let mut start = digits.len();
+    loop {
+        start -= 1;
+        digits[start] = b'0' + (value % 10) as u8;
+        value /= 10;
+        if value == 0 {
+            break;
+        }
+    }
+    unsafe { write(&digits[start..]) }
+}
+
+// The panic handler of programs that do not have a `#[panic_handler]`, it reports the panic and
+// halts the CPU for good
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn _salt_std_panic_handler(
+    message: *const u8,
+    message_len: usize,
+    file: *const u8,
+    file_len: usize,
+    line: u32,
+    column: u32,
+) -> ! {
+    unsafe {
+        write(b"panicked at ");
+        write(slice::from_raw_parts(file, file_len));
+        write(b":");
+        write_u32(line);
+        write(b":");
+        write_u32(column);
+        write(b":\n");
+        write(slice::from_raw_parts(message, message_len));
+        write(b"\n");
+        // A non maskable interrupt can still wake the CPU, so it halts again
+        loop {
+            asm!("cli; hlt", options(nomem, nostack));
+        }
+    }
+}